@@ -0,0 +1,24 @@
+//! Dumps a LightWave object as JSON or RON, e.g. for diffing assets in code review.
+//!
+//! ```sh
+//! cargo run --example dump --features serde -- path/to/file.lwo [json|ron]
+//! ```
+
+use lightwave_3d::LightWaveObject;
+use std::env;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args
+        .next()
+        .expect("usage: dump <path/to/file.lwo> [json|ron]");
+    let format = args.next().unwrap_or_else(|| "json".to_string());
+
+    let object = LightWaveObject::read_file(&path).unwrap();
+    let output = match format.as_str() {
+        "json" => serde_json::to_string_pretty(&object).unwrap(),
+        "ron" => ron::ser::to_string_pretty(&object, ron::ser::PrettyConfig::default()).unwrap(),
+        format => panic!("unknown format \"{}\", expected json or ron", format),
+    };
+    println!("{}", output);
+}
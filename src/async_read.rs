@@ -0,0 +1,111 @@
+use crate::lwo2::tags::Tag;
+use crate::read_options::{with_read_options, LimitError, ReadOptions};
+use crate::LightWaveObject;
+use binrw::{BinRead, BinResult};
+use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+impl LightWaveObject {
+    /// Reads an object from an async reader without blocking on the whole file.
+    ///
+    /// Only the `FORM` header and the eight byte chunk headers are parsed from the stream
+    /// directly. Each chunk body is then read by its length and decoded with the regular binrw
+    /// types, so no more than a single chunk is ever buffered in memory.
+    pub async fn read_async<R>(reader: &mut R) -> BinResult<LightWaveObject>
+    where
+        R: AsyncRead + Unpin,
+    {
+        Self::read_async_with_options(reader, &ReadOptions::default()).await
+    }
+
+    /// Like [LightWaveObject::read_async], but enforces the limits given in `options`.
+    pub async fn read_async_with_options<R>(
+        reader: &mut R,
+        options: &ReadOptions,
+    ) -> BinResult<LightWaveObject>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header).await?;
+        check_magic(&header[0..4], b"FORM", 0)?;
+        check_magic(&header[8..12], b"LWO2", 8)?;
+        let file_size = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let size = file_size as u64 + 8;
+        if size > options.max_file_size {
+            return Err(LimitError::FileSize {
+                size,
+                max: options.max_file_size,
+            }
+            .at(4));
+        }
+
+        let end = 8 + file_size as u64;
+        let mut pos = header.len() as u64;
+        let mut data = vec![];
+        while pos < end {
+            let mut chunk = vec![0u8; 8];
+            reader.read_exact(&mut chunk).await?;
+            let length = u32::from_be_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]) as u64;
+            // checked before anything is buffered, like the synchronous reader does
+            let available = end.saturating_sub(pos + 8);
+            if length > available {
+                return Err(LimitError::ChunkLength { length, available }.at(pos + 8));
+            }
+            let padded_length = length + (length & 1);
+            let read = reader.take(padded_length).read_to_end(&mut chunk).await?;
+            if (read as u64) < length {
+                return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+            }
+
+            let mut chunk_reader = PositionedCursor {
+                cursor: Cursor::new(chunk),
+                offset: pos,
+            };
+            data.push(with_read_options(options, || {
+                Tag::read_be(&mut chunk_reader)
+            })?);
+            pos += 8 + padded_length;
+        }
+
+        Ok(LightWaveObject { file_size, data })
+    }
+}
+
+fn check_magic(found: &[u8], expected: &[u8; 4], pos: u64) -> BinResult<()> {
+    if found == expected {
+        Ok(())
+    } else {
+        Err(binrw::Error::BadMagic {
+            pos,
+            found: Box::new(found.to_vec()),
+        })
+    }
+}
+
+/// A cursor over a single buffered chunk that reports stream positions relative to the
+/// original file, so that error positions match the ones produced by the synchronous reader.
+struct PositionedCursor {
+    cursor: Cursor<Vec<u8>>,
+    offset: u64,
+}
+
+impl Read for PositionedCursor {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Read::read(&mut self.cursor, buf)
+    }
+}
+
+impl Seek for PositionedCursor {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => {
+                SeekFrom::Start(pos.checked_sub(self.offset).ok_or_else(|| {
+                    std::io::Error::new(ErrorKind::InvalidInput, "seek before start of chunk")
+                })?)
+            }
+            pos => pos,
+        };
+        Ok(self.cursor.seek(pos)? + self.offset)
+    }
+}
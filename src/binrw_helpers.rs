@@ -1,13 +1,11 @@
+use crate::lwo2::string::LwoString;
 use crate::lwo2::vx;
-use binrw::{binread, BinRead, BinReaderExt, BinResult, Endian};
+use crate::read_options::{read_options, CountLimit, LimitError};
+use binrw::{BinRead, BinReaderExt, BinResult, Endian};
+use std::cell::Cell;
 use std::io::{Read, Seek};
 use std::iter::from_fn;
 
-#[binread]
-#[br(assert(false, "Not implemented yet"))]
-#[derive(Debug)]
-pub struct BinReadTodo();
-
 pub fn until_size_limit<R, Arg, T, Ret>(
     limit: u64,
 ) -> impl Fn(&mut R, Endian, Arg) -> BinResult<Ret>
@@ -43,6 +41,43 @@ where
     }
 }
 
+/// Reads `n` items like `count`, but fails before reading anything if `n` exceeds the
+/// configured limit
+pub fn count_limited<R, T>(
+    limit: CountLimit,
+    n: usize,
+) -> impl Fn(&mut R, Endian, ()) -> BinResult<Vec<T>>
+where
+    T: for<'a> BinRead<Args<'a> = ()> + 'static,
+    R: Read + Seek,
+{
+    move |reader, endian, args| {
+        limit.check(reader.stream_position()?, n)?;
+        binrw::helpers::count_with(n, default_reader)(reader, endian, args)
+    }
+}
+
+/// Like [until_size_limit], but fails as soon as more items than the configured limit are read
+pub fn until_size_limit_limited<R, Arg, T>(
+    limit: u64,
+    count_limit: CountLimit,
+) -> impl Fn(&mut R, Endian, Arg) -> BinResult<Vec<T>>
+where
+    T: for<'a> BinRead<Args<'a> = Arg>,
+    R: Read + Seek,
+    Arg: Clone,
+{
+    move |reader, endian, args| {
+        let count = Cell::new(0);
+        let read = until_size_limit_with(limit, |reader: &mut R, endian, args| {
+            count.set(count.get() + 1);
+            count_limit.check(reader.stream_position()?, count.get())?;
+            default_reader(reader, endian, args)
+        });
+        read(reader, endian, args)
+    }
+}
+
 pub fn count_with_vx<R>(n: usize) -> impl Fn(&mut R, Endian, ()) -> BinResult<Vec<u32>>
 where
     R: Read + Seek,
@@ -55,23 +90,29 @@ where
 }
 
 /// LightWave uses a string that has an extra null byte if the read amount is uneven
-pub fn lwo_null_string<R>(reader: &mut R, endian: Endian, _args: ()) -> BinResult<String>
+pub fn lwo_null_string<R>(reader: &mut R, endian: Endian, _args: ()) -> BinResult<LwoString>
 where
     R: Read + Seek,
 {
     let mut buf = vec![];
     let pos = reader.stream_position()?;
+    let options = read_options();
     loop {
         match reader.read_type::<u8>(endian)? {
             0 if (reader.stream_position()? - pos) % 2 == 0 => break,
             0 => (),
+            _ if buf.len() >= options.max_string_length => {
+                return Err(LimitError::StringLength {
+                    max: options.max_string_length,
+                }
+                .at(pos))
+            }
             b => buf.push(b),
         }
     }
     // as per spec there should only ever be ascii characters, but of course some people
-    // put other stuff in there.
-    // We'll just ignore this since it's *technically* an issue with the file, not with the parser
-    Ok(String::from_utf8_lossy(buf.as_slice()).to_string())
+    // put other stuff in there, so the raw bytes are kept around.
+    Ok(LwoString::from_raw(buf, options.string_decoder))
 }
 
 fn default_reader<'a, T: BinRead, R: Read + Seek>(
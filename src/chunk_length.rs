@@ -0,0 +1,102 @@
+//! The lengths of chunks as they are stored in their headers, for chunks that are built or
+//! modified in memory rather than read from a file.
+
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::discontinuous_vertex_mapping::DiscontinuousVertexMappings;
+use crate::lwo2::tags::layer::Layer;
+use crate::lwo2::tags::polygon_list::PolygonLists;
+use crate::lwo2::tags::polygon_tag_mapping::PolygonTagMappings;
+use crate::lwo2::tags::tag_strings::TagStrings;
+use crate::lwo2::tags::vertex_mapping::VertexMappings;
+use crate::lwo2::tags::Tag;
+
+/// The size of a null-terminated string, including the padding byte
+pub(crate) fn string_length(string: &LwoString) -> u32 {
+    (string.raw_bytes().len() as u32 + 2) & !1
+}
+
+/// The size of a variable length index
+pub(crate) fn vx_length(index: u32) -> u32 {
+    if index < 0xff00 {
+        2
+    } else {
+        4
+    }
+}
+
+pub(crate) fn layer_length(layer: &Layer) -> u32 {
+    16 + string_length(&layer.name) + if layer.parent.is_some() { 2 } else { 0 }
+}
+
+pub(crate) fn tag_strings_length(tags: &TagStrings) -> u32 {
+    tags.tag_strings.iter().map(string_length).sum()
+}
+
+pub(crate) fn vertex_mappings_length(map: &VertexMappings) -> u32 {
+    6 + string_length(&map.name)
+        + map
+            .mapping
+            .iter()
+            .map(|mapping| vx_length(mapping.vert) + 4 * mapping.value.len() as u32)
+            .sum::<u32>()
+}
+
+pub(crate) fn discontinuous_vertex_mappings_length(map: &DiscontinuousVertexMappings) -> u32 {
+    6 + string_length(&map.name)
+        + map
+            .mappings
+            .iter()
+            .map(|mapping| {
+                vx_length(mapping.vert) + vx_length(mapping.poly) + 4 * mapping.values.len() as u32
+            })
+            .sum::<u32>()
+}
+
+pub(crate) fn polygon_lists_length(polygons: &PolygonLists) -> u32 {
+    4 + polygons
+        .polygons
+        .iter()
+        .map(|polygon| {
+            2 + polygon
+                .vert
+                .iter()
+                .map(|&vert| vx_length(vert))
+                .sum::<u32>()
+        })
+        .sum::<u32>()
+}
+
+pub(crate) fn polygon_tag_mappings_length(tags: &PolygonTagMappings) -> u32 {
+    4 + tags
+        .mappings
+        .iter()
+        .map(|mapping| vx_length(mapping.poly) + 2)
+        .sum::<u32>()
+}
+
+/// The length of a FORM with the given chunks, as stored in its header
+pub(crate) fn form_length(data: &[Tag]) -> u32 {
+    4 + data
+        .iter()
+        .map(|tag| {
+            let length = match tag {
+                Tag::Layer(chunk) => chunk.length,
+                Tag::PointList(chunk) => chunk.length,
+                Tag::VertexMapping(chunk) => chunk.length,
+                Tag::TagStrings(chunk) => chunk.length,
+                Tag::PolygonTagMapping(chunk) => chunk.length,
+                Tag::DiscontinuousVertexMapping(chunk) => chunk.length,
+                Tag::VertexMapParameter(chunk) => chunk.length,
+                Tag::BoundingBox(chunk) => chunk.length,
+                Tag::DescriptionLine(chunk) => chunk.length,
+                Tag::CommentaryText(chunk) => chunk.length,
+                Tag::ThumbnailIconImage(chunk) => chunk.length,
+                Tag::PolygonList(chunk) => chunk.length,
+                Tag::SurfaceDefinition(chunk) => chunk.length,
+                Tag::ImageClip(chunk) => chunk.length,
+                Tag::EnvelopeDefinition(chunk) => chunk.length,
+            };
+            8 + length + (length & 1)
+        })
+        .sum::<u32>()
+}
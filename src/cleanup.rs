@@ -0,0 +1,924 @@
+//! Cleanup passes for layers with overlapping points and junk polygons, such as imported scans.
+//!
+//! Every pass works on the PNTS chunks of one layer along with the chunks that refer to them, and
+//! keeps the point and polygon indices of VMAP, VMAD, PTAG and POLS chunks consistent. Chunk
+//! lengths and the file size are updated as well, but BBOX chunks are left alone, see
+//! [LightWaveObject::refresh_bounding_boxes].
+
+use crate::chunk_length::{
+    discontinuous_vertex_mappings_length, form_length, polygon_lists_length,
+    polygon_tag_mappings_length, vertex_mappings_length,
+};
+use crate::geometry::math::{add, area_vector, distance, dot, length, normalize, scale, sub, Vec3};
+use crate::geometry::mesh::{LayerMesh, PolygonGroup};
+use crate::iff::Chunk;
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::discontinuous_vertex_mapping::{
+    DiscontinuousVertexMapping, DiscontinuousVertexMappings,
+};
+use crate::lwo2::tags::polygon_list::PolygonLists;
+use crate::lwo2::tags::vertex_mapping::{VertexMapKind, VertexMapping};
+use crate::lwo2::tags::Tag;
+use crate::LightWaveObject;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// A polygon whose vertices don't lie in one plane, found by [LayerMesh::non_planar_polygons].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NonPlanarPolygon {
+    pub polygon: u32,
+    /// The largest distance of a vertex from the plane through the center of the polygon
+    pub distance: f32,
+}
+
+impl LightWaveObject {
+    /// Merges the points of a layer that are at most `tolerance` apart into the first of them,
+    /// and returns the number of points removed.
+    ///
+    /// Each merged point keeps its own VMAP values, or takes those of the first point that has
+    /// any. Where the merged points disagree, the values of the others are kept for their
+    /// polygons as VMAD entries, so that UV seams survive. Morphs can't vary per polygon and keep
+    /// the first value. Polygons can end up with repeated vertices, see
+    /// [LightWaveObject::remove_degenerate_polygons].
+    pub fn weld_points(&mut self, layer: u16, tolerance: f32) -> usize {
+        let mut removed = 0;
+        for range in self.point_ranges(layer).into_iter().rev() {
+            removed += weld_range(&mut self.data, range, tolerance);
+        }
+        self.file_size = form_length(&self.data);
+        removed
+    }
+
+    /// Removes the points of a layer that no polygon uses, along with their VMAP and VMAD
+    /// values, and returns the number of points removed
+    pub fn remove_unused_points(&mut self, layer: u16) -> usize {
+        let mut removed = 0;
+        for range in self.point_ranges(layer).into_iter().rev() {
+            let data = &mut self.data[range];
+            let Tag::PointList(points) = &data[0] else {
+                continue;
+            };
+            let mut used = vec![false; points.point_location.len()];
+            for tag in data.iter() {
+                if let Tag::PolygonList(polygons) = tag {
+                    for &vert in polygons.polygons.iter().flat_map(|polygon| &polygon.vert) {
+                        if let Some(used) = used.get_mut(vert as usize) {
+                            *used = true;
+                        }
+                    }
+                }
+            }
+            let mut count = 0;
+            let remap: Vec<Option<u32>> = used
+                .iter()
+                .map(|&used| {
+                    used.then(|| {
+                        count += 1;
+                        count - 1
+                    })
+                })
+                .collect();
+            let points = points
+                .point_location
+                .iter()
+                .zip(&used)
+                .filter(|(_, &used)| used)
+                .map(|(&point, _)| point)
+                .collect();
+            removed += used.len() - count as usize;
+            remap_points(data, &remap, points);
+        }
+        self.file_size = form_length(&self.data);
+        removed
+    }
+
+    /// Removes the FACE and PTCH polygons of a layer that repeat a vertex right after itself or
+    /// whose area is at most `min_area`, along with their polygon tags and VMAD values, and
+    /// returns the number of polygons removed.
+    ///
+    /// Repeating vertices that aren't next to each other is how LightWave cuts holes into
+    /// polygons, so those are kept. Polygons with fewer than three vertices, which are used as
+    /// points and lines, are only removed if they repeat a vertex.
+    pub fn remove_degenerate_polygons(&mut self, layer: u16, min_area: f32) -> usize {
+        self.remove_polygons(layer, |points, polygons| {
+            if !polygons.kind.is_surface() {
+                return vec![true; polygons.polygons.len()];
+            }
+            polygons
+                .polygons
+                .iter()
+                .map(|polygon| {
+                    let vert = &polygon.vert;
+                    let repeats = vert.len() > 1
+                        && (0..vert.len())
+                            .any(|corner| vert[corner] == vert[(corner + 1) % vert.len()]);
+                    let corners: Option<Vec<Vec3>> = vert
+                        .iter()
+                        .map(|&vert| points.get(vert as usize).copied())
+                        .collect();
+                    let is_flat = match corners {
+                        Some(corners) if corners.len() >= 3 => {
+                            length(area_vector(&corners)) / 2.0 <= min_area
+                        }
+                        _ => false,
+                    };
+                    !repeats && !is_flat
+                })
+                .collect()
+        })
+    }
+
+    /// Removes polygons of a layer that use the same vertices in the same order as an earlier
+    /// polygon of the same POLS chunk, and returns the number of polygons removed. FACE and PTCH
+    /// polygons are closed, so they are duplicates no matter which vertex they start at, while
+    /// the start of curves, bones and other polygons matters. Polygons with the reverse order
+    /// face the other way and are kept.
+    pub fn remove_duplicate_polygons(&mut self, layer: u16) -> usize {
+        self.remove_polygons(layer, |_, polygons| {
+            let is_surface = polygons.kind.is_surface();
+            let mut seen = HashSet::new();
+            polygons
+                .polygons
+                .iter()
+                .map(|polygon| {
+                    let vert = &polygon.vert;
+                    let rotation = if is_surface {
+                        (0..vert.len())
+                            .min_by(|&a, &b| {
+                                vert[a..]
+                                    .iter()
+                                    .chain(&vert[..a])
+                                    .cmp(vert[b..].iter().chain(&vert[..b]))
+                            })
+                            .unwrap_or(0)
+                    } else {
+                        0
+                    };
+                    let key: Vec<u32> = vert[rotation..]
+                        .iter()
+                        .chain(&vert[..rotation])
+                        .copied()
+                        .collect();
+                    seen.insert((polygon.flags, key))
+                })
+                .collect()
+        })
+    }
+
+    /// Removes the polygons for which `keep` returns `false`, given the points and every POLS of
+    /// a layer, and returns the number of polygons removed
+    fn remove_polygons(
+        &mut self,
+        layer: u16,
+        mut keep: impl FnMut(&[Vec3], &PolygonLists) -> Vec<bool>,
+    ) -> usize {
+        let mut removed = 0;
+        for range in self.point_ranges(layer) {
+            let data = &mut self.data[range];
+            let Tag::PointList(points) = &data[0] else {
+                continue;
+            };
+            let points = points.point_location.clone();
+            for index in 0..data.len() {
+                let Tag::PolygonList(polygons) = &data[index] else {
+                    continue;
+                };
+                let keep = keep(&points, &polygons.data);
+                removed += keep.iter().filter(|&&keep| !keep).count();
+                retain_polygons(data, index, &keep);
+            }
+        }
+        self.file_size = form_length(&self.data);
+        removed
+    }
+
+    /// The ranges of chunks from every PNTS of the layer with the given number to the next PNTS
+    /// or LAYR
+    fn point_ranges(&self, layer: u16) -> Vec<Range<usize>> {
+        let mut ranges = vec![];
+        let mut in_layer = false;
+        let mut start = None;
+        for (index, tag) in self.data.iter().enumerate() {
+            match tag {
+                Tag::Layer(chunk) => {
+                    ranges.extend(start.take().map(|start| start..index));
+                    in_layer = chunk.number == layer;
+                }
+                Tag::PointList(_) if in_layer => {
+                    ranges.extend(start.replace(index).map(|start| start..index));
+                }
+                _ => (),
+            }
+        }
+        ranges.extend(start.map(|start| start..self.data.len()));
+        ranges
+    }
+}
+
+impl LayerMesh<'_> {
+    /// All FACE or PTCH polygons with a vertex that is further than `threshold` from the plane
+    /// through the center of the polygon, perpendicular to its average normal
+    pub fn non_planar_polygons(
+        &self,
+        group: &PolygonGroup,
+        threshold: f32,
+    ) -> Vec<NonPlanarPolygon> {
+        if !group.polygons.kind.is_surface() {
+            return vec![];
+        }
+        let points = &self.points.point_location;
+        let mut non_planar = vec![];
+        for (index, polygon) in group.polygons.polygons.iter().enumerate() {
+            let corners: Option<Vec<Vec3>> = polygon
+                .vert
+                .iter()
+                .map(|&vert| points.get(vert as usize).copied())
+                .collect();
+            let Some(corners) = corners.filter(|corners| corners.len() > 3) else {
+                continue;
+            };
+            let Some(normal) = normalize(area_vector(&corners)) else {
+                continue;
+            };
+            let center = scale(
+                corners
+                    .iter()
+                    .fold([0.0; 3], |sum, &corner| add(sum, corner)),
+                1.0 / corners.len() as f32,
+            );
+            let distance = corners
+                .iter()
+                .map(|&corner| dot(sub(corner, center), normal).abs())
+                .fold(0.0, f32::max);
+            if distance > threshold {
+                non_planar.push(NonPlanarPolygon {
+                    polygon: index as u32,
+                    distance,
+                });
+            }
+        }
+        non_planar
+    }
+}
+
+/// See [LightWaveObject::weld_points]. `range` starts at a PNTS chunk.
+fn weld_range(data: &mut Vec<Tag>, range: Range<usize>, tolerance: f32) -> usize {
+    let Tag::PointList(points) = &data[range.start] else {
+        return 0;
+    };
+    let points = &points.point_location.clone();
+
+    // the first point each point is merged into, found through a grid of the kept points
+    let cell = |point: Vec3| -> [i64; 3] {
+        if tolerance > 0.0 {
+            point.map(|value| (value / tolerance).floor() as i64)
+        } else {
+            point.map(|value| (value + 0.0).to_bits() as i64)
+        }
+    };
+    let reach = if tolerance > 0.0 { 1 } else { 0 };
+    let mut grid: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+    let mut kept = Vec::with_capacity(points.len());
+    let mut remap = Vec::with_capacity(points.len());
+    for (index, &point) in points.iter().enumerate() {
+        let [x, y, z] = cell(point);
+        let mut target = None;
+        'search: for dx in -reach..=reach {
+            for dy in -reach..=reach {
+                for dz in -reach..=reach {
+                    let neighbors = grid.get(&[x + dx, y + dy, z + dz]);
+                    if let Some(&other) = neighbors.into_iter().flatten().find(|&&other| {
+                        distance(points[kept[other as usize] as usize], point) <= tolerance
+                    }) {
+                        target = Some(other);
+                        break 'search;
+                    }
+                }
+            }
+        }
+        remap.push(Some(target.unwrap_or_else(|| {
+            kept.push(index as u32);
+            let new = kept.len() as u32 - 1;
+            grid.entry([x, y, z]).or_default().push(new);
+            new
+        })));
+    }
+    let removed = points.len() - kept.len();
+    if removed == 0 {
+        return 0;
+    }
+    let new_points = kept.iter().map(|&index| points[index as usize]).collect();
+
+    // merge the VMAP values, and note those that have to become VMAD entries
+    let mut seams: Vec<Seam> = vec![];
+    for tag in &mut data[range.clone()] {
+        let Tag::VertexMapping(chunk) = tag else {
+            continue;
+        };
+        let map = &mut chunk.data;
+        let is_kept = |vert: u32| kept.get(remap[vert as usize].unwrap() as usize) == Some(&vert);
+        let mut values: HashMap<u32, &Vec<f32>> = HashMap::new();
+        for mapping in map
+            .mapping
+            .iter()
+            .filter(|mapping| (mapping.vert as usize) < points.len())
+        {
+            let new = remap[mapping.vert as usize].unwrap();
+            if is_kept(mapping.vert) || !values.contains_key(&new) {
+                values.insert(new, &mapping.value);
+            }
+        }
+        let varies_per_polygon = map.dimension > 0
+            && !matches!(
+                map.kind,
+                VertexMapKind::Morph | VertexMapKind::AbsoluteMorph
+            );
+        let mut mapping = vec![];
+        let mut written = HashSet::new();
+        for source in &map.mapping {
+            let Some(&Some(new)) = remap.get(source.vert as usize) else {
+                mapping.push(source.clone());
+                continue;
+            };
+            let value = values[&new];
+            if varies_per_polygon && *value != source.value {
+                seams.push(Seam {
+                    kind: map.kind,
+                    dimension: map.dimension,
+                    name: map.name.clone(),
+                    old: source.vert,
+                    new,
+                    values: source.value.clone(),
+                });
+            }
+            if written.insert(new) {
+                // the kept point, which is renumbered along with the other chunks
+                mapping.push(VertexMapping {
+                    vert: kept[new as usize],
+                    value: value.clone(),
+                });
+            }
+        }
+        map.mapping = mapping;
+    }
+
+    // the polygons that used a point with different values, unless a VMAD already covers them
+    let seam_points: HashSet<u32> = seams.iter().map(|seam| seam.old).collect();
+    let mut additions: Vec<(usize, Seam, u32)> = vec![];
+    for index in range.clone() {
+        let Tag::PolygonList(polygons) = &data[index] else {
+            continue;
+        };
+        let end = next_polygon_list(data, index, range.end);
+        let mut point_polygons: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (poly, polygon) in polygons.polygons.iter().enumerate() {
+            let poly = poly as u32;
+            for &vert in polygon
+                .vert
+                .iter()
+                .filter(|vert| seam_points.contains(vert))
+            {
+                let polys = point_polygons.entry(vert).or_default();
+                if polys.last() != Some(&poly) {
+                    polys.push(poly);
+                }
+            }
+        }
+        let covered: HashSet<(VertexMapKind, &LwoString, u32, u32)> = data[index + 1..end]
+            .iter()
+            .filter_map(|tag| match tag {
+                Tag::DiscontinuousVertexMapping(map) => Some(map),
+                _ => None,
+            })
+            .flat_map(|map| {
+                map.mappings
+                    .iter()
+                    .map(|mapping| (map.kind, &map.name, mapping.vert, mapping.poly))
+            })
+            .collect();
+        for seam in &seams {
+            for &poly in point_polygons.get(&seam.old).into_iter().flatten() {
+                if !covered.contains(&(seam.kind, &seam.name, seam.old, poly)) {
+                    additions.push((index, seam.clone(), poly));
+                }
+            }
+        }
+    }
+
+    remap_points(&mut data[range.clone()], &remap, new_points);
+
+    let mut range_end = range.end;
+    for (index, seam, poly) in additions.into_iter().rev() {
+        let end = next_polygon_list(data, index, range_end);
+        let existing = (index + 1..end).find(|&existing| {
+            matches!(&data[existing], Tag::DiscontinuousVertexMapping(map)
+                if map.kind == seam.kind && map.dimension == seam.dimension && map.name == seam.name)
+        });
+        let existing = existing.unwrap_or_else(|| {
+            data.insert(
+                end,
+                Tag::DiscontinuousVertexMapping(Chunk {
+                    length: 0,
+                    data: DiscontinuousVertexMappings {
+                        kind: seam.kind,
+                        dimension: seam.dimension,
+                        name: seam.name.clone(),
+                        mappings: vec![],
+                    },
+                }),
+            );
+            range_end += 1;
+            end
+        });
+        if let Tag::DiscontinuousVertexMapping(chunk) = &mut data[existing] {
+            chunk.data.mappings.push(DiscontinuousVertexMapping {
+                vert: seam.new,
+                poly,
+                values: seam.values,
+            });
+        }
+    }
+    for tag in &mut data[range.start..range_end] {
+        if let Tag::DiscontinuousVertexMapping(chunk) = tag {
+            chunk.length = discontinuous_vertex_mappings_length(&chunk.data);
+        }
+    }
+    removed
+}
+
+/// A VMAP value of a merged point that differs from the value of the point it was merged into
+#[derive(Debug, Clone)]
+struct Seam {
+    kind: VertexMapKind,
+    dimension: u16,
+    name: LwoString,
+    old: u32,
+    new: u32,
+    values: Vec<f32>,
+}
+
+/// The index of the next POLS or the end of the range, which ends the chunks that refer to the
+/// POLS at `index`
+fn next_polygon_list(data: &[Tag], index: usize, end: usize) -> usize {
+    (index + 1..end)
+        .find(|&next| matches!(data[next], Tag::PolygonList(_)))
+        .unwrap_or(end)
+}
+
+/// Replaces the points of the PNTS at the start of `data` and rewrites the point indices of the
+/// chunks after it. VMAP and VMAD values of removed points are dropped, and indices past the end
+/// of the old point list stay past the end of the new one.
+fn remap_points(data: &mut [Tag], remap: &[Option<u32>], points: Vec<Vec3>) {
+    let new_count = points.len() as u32;
+    let map = |vert: u32| match remap.get(vert as usize) {
+        Some(&new) => new,
+        None => Some(vert - remap.len() as u32 + new_count),
+    };
+    for tag in data {
+        match tag {
+            Tag::PointList(chunk) => {
+                chunk.data.point_location = points.clone();
+                chunk.length = 12 * new_count;
+            }
+            Tag::VertexMapping(chunk) => {
+                chunk
+                    .data
+                    .mapping
+                    .retain_mut(|mapping| match map(mapping.vert) {
+                        Some(vert) => {
+                            mapping.vert = vert;
+                            true
+                        }
+                        None => false,
+                    });
+                chunk.length = vertex_mappings_length(&chunk.data);
+            }
+            Tag::DiscontinuousVertexMapping(chunk) => {
+                chunk
+                    .data
+                    .mappings
+                    .retain_mut(|mapping| match map(mapping.vert) {
+                        Some(vert) => {
+                            mapping.vert = vert;
+                            true
+                        }
+                        None => false,
+                    });
+                chunk.length = discontinuous_vertex_mappings_length(&chunk.data);
+            }
+            Tag::PolygonList(chunk) => {
+                for vert in chunk
+                    .data
+                    .polygons
+                    .iter_mut()
+                    .flat_map(|polygon| &mut polygon.vert)
+                {
+                    *vert = map(*vert).unwrap_or(*vert);
+                }
+                chunk.length = polygon_lists_length(&chunk.data);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Removes polygons from the POLS at `index` and rewrites the polygon indices of the PTAG and
+/// VMAD chunks that refer to it
+fn retain_polygons(data: &mut [Tag], index: usize, keep: &[bool]) {
+    if keep.iter().all(|&keep| keep) {
+        return;
+    }
+    let mut count = 0;
+    let remap: Vec<Option<u32>> = keep
+        .iter()
+        .map(|&keep| {
+            keep.then(|| {
+                count += 1;
+                count - 1
+            })
+        })
+        .collect();
+    let map = |poly: u32| remap.get(poly as usize).copied().unwrap_or(Some(poly));
+
+    let end = next_polygon_list(data, index, data.len());
+    if let Tag::PolygonList(chunk) = &mut data[index] {
+        let mut keep = keep.iter();
+        chunk
+            .data
+            .polygons
+            .retain(|_| keep.next().copied().unwrap_or(true));
+        chunk.length = polygon_lists_length(&chunk.data);
+    }
+    for tag in &mut data[index + 1..end] {
+        match tag {
+            Tag::PolygonTagMapping(chunk) => {
+                chunk
+                    .data
+                    .mappings
+                    .retain_mut(|mapping| match map(mapping.poly) {
+                        Some(poly) => {
+                            mapping.poly = poly;
+                            true
+                        }
+                        None => false,
+                    });
+                chunk.length = polygon_tag_mappings_length(&chunk.data);
+            }
+            Tag::DiscontinuousVertexMapping(chunk) => {
+                chunk
+                    .data
+                    .mappings
+                    .retain_mut(|mapping| match map(mapping.poly) {
+                        Some(poly) => {
+                            mapping.poly = poly;
+                            true
+                        }
+                        None => false,
+                    });
+                chunk.length = discontinuous_vertex_mappings_length(&chunk.data);
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_length::layer_length;
+    use crate::lwo2::tags::layer::Layer;
+    use crate::lwo2::tags::point_list::PointList;
+    use crate::lwo2::tags::polygon_list::{PolygonKind, PolygonList};
+    use crate::lwo2::tags::polygon_tag_mapping::{
+        PolygonTagKind, PolygonTagMapping, PolygonTagMappings,
+    };
+    use crate::lwo2::tags::vertex_mapping::VertexMappings;
+
+    /// A single layer with the given points, FACE polygons, polygon tags and vertex maps
+    fn object(
+        points: Vec<Vec3>,
+        polygons: Vec<Vec<u32>>,
+        tags: Vec<(u32, u16)>,
+        maps: Vec<VertexMappings>,
+    ) -> LightWaveObject {
+        let layer = Layer {
+            number: 0,
+            flags: 0,
+            pivot: [0.0; 3],
+            name: LwoString::default(),
+            parent: None,
+        };
+        let mut data = vec![
+            Tag::Layer(Chunk {
+                length: layer_length(&layer),
+                data: layer,
+            }),
+            Tag::PointList(Chunk {
+                length: 12 * points.len() as u32,
+                data: PointList {
+                    point_location: points,
+                },
+            }),
+        ];
+        data.extend(maps.into_iter().map(|map| {
+            Tag::VertexMapping(Chunk {
+                length: vertex_mappings_length(&map),
+                data: map,
+            })
+        }));
+        let polygons = PolygonLists {
+            kind: PolygonKind::Face,
+            polygons: polygons
+                .into_iter()
+                .map(|vert| PolygonList { flags: 0, vert })
+                .collect(),
+        };
+        data.push(Tag::PolygonList(Chunk {
+            length: polygon_lists_length(&polygons),
+            data: polygons,
+        }));
+        if !tags.is_empty() {
+            let tags = PolygonTagMappings {
+                kind: PolygonTagKind::SmoothingGroup,
+                mappings: tags
+                    .into_iter()
+                    .map(|(poly, tag)| PolygonTagMapping { poly, tag })
+                    .collect(),
+            };
+            data.push(Tag::PolygonTagMapping(Chunk {
+                length: polygon_tag_mappings_length(&tags),
+                data: tags,
+            }));
+        }
+        LightWaveObject {
+            file_size: form_length(&data),
+            data,
+        }
+    }
+
+    fn uv_map(values: &[(u32, [f32; 2])]) -> VertexMappings {
+        VertexMappings {
+            kind: VertexMapKind::Uv,
+            dimension: 2,
+            name: LwoString::new("UV"),
+            mapping: values
+                .iter()
+                .map(|&(vert, value)| VertexMapping {
+                    vert,
+                    value: value.to_vec(),
+                })
+                .collect(),
+        }
+    }
+
+    fn set_polygon_kind(object: &mut LightWaveObject, kind: PolygonKind) {
+        for tag in &mut object.data {
+            if let Tag::PolygonList(chunk) = tag {
+                chunk.kind = kind;
+            }
+        }
+    }
+
+    fn polygons(object: &LightWaveObject) -> Vec<Vec<u32>> {
+        object
+            .data
+            .iter()
+            .find_map(|tag| match tag {
+                Tag::PolygonList(chunk) => Some(&chunk.polygons),
+                _ => None,
+            })
+            .unwrap()
+            .iter()
+            .map(|polygon| polygon.vert.clone())
+            .collect()
+    }
+
+    fn uvs(object: &LightWaveObject) -> Vec<(u32, Vec<f32>)> {
+        object
+            .data
+            .iter()
+            .find_map(|tag| match tag {
+                Tag::VertexMapping(chunk) => Some(&chunk.mapping),
+                _ => None,
+            })
+            .unwrap()
+            .iter()
+            .map(|mapping| (mapping.vert, mapping.value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn weld_keeps_uv_seams() {
+        // two triangles sharing an edge, with the shared points duplicated and one of them
+        // mapped to a different UV in the second triangle
+        let mut object = object(
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 1.0, 0.0],
+            ],
+            vec![vec![0, 1, 2], vec![3, 5, 4]],
+            vec![],
+            vec![uv_map(&[
+                (0, [0.0, 0.0]),
+                (1, [1.0, 0.0]),
+                (2, [0.0, 1.0]),
+                (3, [1.0, 0.0]),
+                (4, [0.5, 1.0]),
+                (5, [1.0, 1.0]),
+            ])],
+        );
+
+        assert_eq!(object.weld_points(0, 0.001), 2);
+        assert_eq!(polygons(&object), vec![vec![0, 1, 2], vec![1, 3, 2]]);
+        assert_eq!(
+            uvs(&object),
+            vec![
+                (0, vec![0.0, 0.0]),
+                (1, vec![1.0, 0.0]),
+                (2, vec![0.0, 1.0]),
+                (3, vec![1.0, 1.0]),
+            ]
+        );
+        let seams: Vec<_> = object
+            .data
+            .iter()
+            .filter_map(|tag| match tag {
+                Tag::DiscontinuousVertexMapping(chunk) => Some(chunk),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(seams.len(), 1);
+        assert_eq!(
+            seams[0].length,
+            discontinuous_vertex_mappings_length(seams[0])
+        );
+        assert_eq!(seams[0].name, "UV");
+        let entries: Vec<_> = seams[0]
+            .mappings
+            .iter()
+            .map(|mapping| (mapping.vert, mapping.poly, mapping.values.clone()))
+            .collect();
+        assert_eq!(entries, vec![(2, 1, vec![0.5, 1.0])]);
+        assert_eq!(object.file_size, form_length(&object.data));
+        assert!(object.validate().is_ok());
+    }
+
+    #[test]
+    fn remove_unused_points_remaps_indices() {
+        let mut object = object(
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [2.0, 0.0, 0.0],
+                [3.0, 0.0, 0.0],
+                [4.0, 0.0, 0.0],
+            ],
+            vec![vec![1, 3, 4]],
+            vec![],
+            vec![uv_map(&[
+                (0, [0.0, 0.0]),
+                (2, [2.0, 0.0]),
+                (3, [3.0, 0.0]),
+                (4, [4.0, 0.0]),
+            ])],
+        );
+
+        assert_eq!(object.remove_unused_points(0), 2);
+        assert_eq!(polygons(&object), vec![vec![0, 1, 2]]);
+        assert_eq!(uvs(&object), vec![(1, vec![3.0, 0.0]), (2, vec![4.0, 0.0])]);
+        let Tag::PointList(points) = &object.data[1] else {
+            panic!("expected a point list");
+        };
+        assert_eq!(
+            points.point_location,
+            vec![[1.0, 0.0, 0.0], [3.0, 0.0, 0.0], [4.0, 0.0, 0.0]]
+        );
+        assert_eq!(points.length, 36);
+        assert_eq!(object.file_size, form_length(&object.data));
+        assert!(object.validate().is_ok());
+    }
+
+    #[test]
+    fn remove_duplicate_polygons_by_rotation() {
+        let mut object = object(
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            vec![vec![0, 1, 2], vec![1, 2, 0], vec![2, 1, 0], vec![2, 0, 1]],
+            vec![(2, 7), (3, 8)],
+            vec![],
+        );
+
+        assert_eq!(object.remove_duplicate_polygons(0), 2);
+        // the reversed polygon faces the other way and is kept, along with its tag
+        assert_eq!(polygons(&object), vec![vec![0, 1, 2], vec![2, 1, 0]]);
+        let tags = object
+            .data
+            .iter()
+            .find_map(|tag| match tag {
+                Tag::PolygonTagMapping(chunk) => Some(chunk),
+                _ => None,
+            })
+            .unwrap();
+        let mappings: Vec<_> = tags
+            .mappings
+            .iter()
+            .map(|mapping| (mapping.poly, mapping.tag))
+            .collect();
+        assert_eq!(mappings, vec![(1, 7)]);
+        assert_eq!(tags.length, polygon_tag_mappings_length(tags));
+        assert_eq!(object.file_size, form_length(&object.data));
+    }
+
+    #[test]
+    fn remove_duplicate_curves_by_exact_order() {
+        let mut object = object(
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]],
+            vec![vec![0, 1, 2], vec![1, 2, 0], vec![0, 1, 2]],
+            vec![],
+            vec![],
+        );
+        set_polygon_kind(&mut object, PolygonKind::Curve);
+
+        // a curve starting at another point takes a different path
+        assert_eq!(object.remove_duplicate_polygons(0), 1);
+        assert_eq!(polygons(&object), vec![vec![0, 1, 2], vec![1, 2, 0]]);
+    }
+
+    #[test]
+    fn remove_degenerate_polygons_keeps_holes() {
+        let mut object = object(
+            vec![
+                [0.0, 0.0, 0.0],
+                [3.0, 0.0, 0.0],
+                [3.0, 3.0, 0.0],
+                [0.0, 3.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [2.0, 1.0, 0.0],
+                [2.0, 2.0, 0.0],
+                [1.0, 2.0, 0.0],
+                [6.0, 0.0, 0.0],
+            ],
+            vec![
+                // a square with a square hole, connected through the repeated points 0 and 4
+                vec![0, 1, 2, 3, 0, 4, 7, 6, 5, 4],
+                vec![0, 1, 1, 2],
+                vec![0, 1, 8],
+                vec![4, 5, 6],
+                vec![5, 6, 7, 5],
+            ],
+            vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)],
+            vec![],
+        );
+
+        assert_eq!(object.remove_degenerate_polygons(0, 0.01), 3);
+        assert_eq!(
+            polygons(&object),
+            vec![vec![0, 1, 2, 3, 0, 4, 7, 6, 5, 4], vec![4, 5, 6]]
+        );
+        let tags = object
+            .data
+            .iter()
+            .find_map(|tag| match tag {
+                Tag::PolygonTagMapping(chunk) => Some(chunk),
+                _ => None,
+            })
+            .unwrap();
+        let mappings: Vec<_> = tags
+            .mappings
+            .iter()
+            .map(|mapping| (mapping.poly, mapping.tag))
+            .collect();
+        assert_eq!(mappings, vec![(0, 1), (1, 4)]);
+        assert_eq!(object.file_size, form_length(&object.data));
+    }
+
+    #[test]
+    fn non_planar_polygons() {
+        let object = object(
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 1.0, 0.5],
+            ],
+            vec![vec![0, 1, 2, 3], vec![0, 1, 4, 3], vec![0, 1, 4]],
+            vec![],
+            vec![],
+        );
+        let meshes = object.layer_meshes();
+        let mesh = &meshes[0];
+
+        let non_planar = mesh.non_planar_polygons(&mesh.polygon_groups[0], 0.01);
+        assert_eq!(non_planar.len(), 1);
+        assert_eq!(non_planar[0].polygon, 1);
+        // every corner of the warped quad is 1/√72 away from the plane through its center
+        assert!((non_planar[0].distance - 1.0 / 72f32.sqrt()).abs() < 1e-6);
+        assert!(mesh
+            .non_planar_polygons(&mesh.polygon_groups[0], 0.2)
+            .is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::chunk_length::form_length;
+use crate::geometry::math::{add, Vec3};
+use crate::geometry::mesh::LayerMesh;
+use crate::iff::Chunk;
+use crate::lwo2::tags::bounding_box::BoundingBox;
+use crate::lwo2::tags::layer::Layer;
+use crate::lwo2::tags::Tag;
+use crate::LightWaveObject;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BoundsOptions {
+    /// Whether the bounds also cover every morph target when it is fully applied
+    pub include_morphs: bool,
+}
+
+/// The bounds of the points of one layer, relative to its pivot like the points themselves.
+#[derive(Debug, Clone)]
+pub struct LayerBounds<'a> {
+    /// The layer, or `None` for data before the first LAYR
+    pub layer: Option<&'a Layer>,
+    /// The bounds, or `None` if the layer has no points
+    pub bounds: Option<BoundingBox>,
+}
+
+/// A BBOX chunk that doesn't match the points of its layer, found by
+/// [LightWaveObject::stale_bounding_boxes].
+#[derive(Debug, Clone)]
+pub struct StaleBoundingBox {
+    /// Index of the BBOX chunk in [LightWaveObject::data]
+    pub tag_index: usize,
+    /// The layer number the chunk belongs to, if any layer was started before it
+    pub layer: Option<u16>,
+    pub stored: BoundingBox,
+    /// The bounds of the points, or `None` if the layer has no points
+    pub actual: Option<BoundingBox>,
+}
+
+impl LayerMesh<'_> {
+    /// The bounds of the points, or `None` if there are none
+    pub fn bounds(&self, options: &BoundsOptions) -> Option<BoundingBox> {
+        let base = &self.points.point_location;
+        let mut bounds = None;
+        for &point in base {
+            include(&mut bounds, point);
+        }
+        if options.include_morphs {
+            for target in self.morph_targets() {
+                for (index, displacement) in target.displacements(base) {
+                    include(&mut bounds, add(base[index], displacement));
+                }
+            }
+        }
+        bounds
+    }
+}
+
+impl LightWaveObject {
+    /// The bounds of every layer, in file order. Data before the first LAYR is only listed if
+    /// there are points before it.
+    pub fn layer_bounds(&self, options: &BoundsOptions) -> Vec<LayerBounds<'_>> {
+        let bounds = self.segment_bounds(options);
+        let has_unlayered_points = self.layer_meshes().iter().any(|mesh| mesh.layer.is_none());
+        let layers = self.data.iter().filter_map(|tag| match tag {
+            Tag::Layer(chunk) => Some(&chunk.data),
+            _ => None,
+        });
+        std::iter::once(None)
+            .chain(layers.map(Some))
+            .zip(bounds)
+            .filter(|(layer, _)| layer.is_some() || has_unlayered_points)
+            .map(|(layer, bounds)| LayerBounds { layer, bounds })
+            .collect()
+    }
+
+    /// The bounds of all layers in object space, that is with the pivot of each layer applied
+    pub fn bounds(&self, options: &BoundsOptions) -> Option<BoundingBox> {
+        let mut bounds = None;
+        for layer in self.layer_bounds(options) {
+            let pivot = layer.layer.map_or([0.0; 3], |layer| layer.pivot);
+            if let Some(layer) = layer.bounds {
+                include(&mut bounds, add(layer.min, pivot));
+                include(&mut bounds, add(layer.max, pivot));
+            }
+        }
+        bounds
+    }
+
+    /// All BBOX chunks whose corners differ from the bounds of the points in their layer by more
+    /// than `tolerance`, or that belong to a layer without points.
+    pub fn stale_bounding_boxes(
+        &self,
+        options: &BoundsOptions,
+        tolerance: f32,
+    ) -> Vec<StaleBoundingBox> {
+        let bounds = self.segment_bounds(options);
+        let mut segment = 0;
+        let mut layer = None;
+        let mut stale = vec![];
+        for (tag_index, tag) in self.data.iter().enumerate() {
+            match tag {
+                Tag::Layer(chunk) => {
+                    segment += 1;
+                    layer = Some(chunk.data.number);
+                }
+                Tag::BoundingBox(chunk) => {
+                    let stored = &chunk.data;
+                    let actual = &bounds[segment];
+                    let matches = actual.as_ref().is_some_and(|actual| {
+                        [0, 1, 2].iter().all(|&axis| {
+                            (actual.min[axis] - stored.min[axis]).abs() <= tolerance
+                                && (actual.max[axis] - stored.max[axis]).abs() <= tolerance
+                        })
+                    });
+                    if !matches {
+                        stale.push(StaleBoundingBox {
+                            tag_index,
+                            layer,
+                            stored: stored.clone(),
+                            actual: actual.clone(),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+        stale
+    }
+
+    /// Brings the BBOX chunks of all layers up to date with their points, in place. This crate
+    /// doesn't write objects, so this is meant to be called on an object whose points were
+    /// edited before it is serialized.
+    ///
+    /// Layers with points but without a BBOX get one right after their first PNTS, and BBOX
+    /// chunks of layers without points are removed. The file size is updated to match.
+    pub fn refresh_bounding_boxes(&mut self, options: &BoundsOptions) {
+        let bounds = self.segment_bounds(options);
+        let mut segment = 0;
+        let mut has_bounding_box = vec![false; bounds.len()];
+        let mut first_points = vec![None; bounds.len()];
+        let mut index = 0;
+        while index < self.data.len() {
+            match &mut self.data[index] {
+                Tag::Layer(_) => segment += 1,
+                Tag::PointList(_) => {
+                    first_points[segment].get_or_insert(index);
+                }
+                Tag::BoundingBox(chunk) => match &bounds[segment] {
+                    Some(bounds) => {
+                        chunk.data = bounds.clone();
+                        has_bounding_box[segment] = true;
+                    }
+                    None => {
+                        self.data.remove(index);
+                        continue;
+                    }
+                },
+                _ => (),
+            }
+            index += 1;
+        }
+
+        for segment in (0..bounds.len()).rev() {
+            if let (Some(bounds), Some(index), false) = (
+                &bounds[segment],
+                first_points[segment],
+                has_bounding_box[segment],
+            ) {
+                self.data.insert(
+                    index + 1,
+                    Tag::BoundingBox(Chunk {
+                        length: 24,
+                        data: bounds.clone(),
+                    }),
+                );
+            }
+        }
+        self.file_size = form_length(&self.data);
+    }
+
+    /// The bounds of the data before the first LAYR, followed by the bounds of every LAYR
+    fn segment_bounds(&self, options: &BoundsOptions) -> Vec<Option<BoundingBox>> {
+        let layers: Vec<&Layer> = self
+            .data
+            .iter()
+            .filter_map(|tag| match tag {
+                Tag::Layer(chunk) => Some(&chunk.data),
+                _ => None,
+            })
+            .collect();
+        let mut bounds = vec![None; layers.len() + 1];
+        for mesh in self.layer_meshes() {
+            let segment = mesh.layer.map_or(0, |layer| {
+                layers
+                    .iter()
+                    .position(|&other| std::ptr::eq(other, layer))
+                    .map_or(0, |index| index + 1)
+            });
+            if let Some(mesh) = mesh.bounds(options) {
+                include(&mut bounds[segment], mesh.min);
+                include(&mut bounds[segment], mesh.max);
+            }
+        }
+        bounds
+    }
+}
+
+fn include(bounds: &mut Option<BoundingBox>, point: Vec3) {
+    match bounds {
+        Some(bounds) => {
+            bounds.min = [0, 1, 2].map(|axis| bounds.min[axis].min(point[axis]));
+            bounds.max = [0, 1, 2].map(|axis| bounds.max[axis].max(point[axis]));
+        }
+        None => {
+            *bounds = Some(BoundingBox {
+                min: point,
+                max: point,
+            })
+        }
+    }
+}
@@ -0,0 +1,499 @@
+use crate::geometry::math::{add, cross, dot, scale, sub, Vec3};
+use crate::geometry::mesh::LayerMesh;
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::polygon_list::PolygonKind;
+use crate::lwo2::tags::polygon_tag_mapping::PolygonTagKind;
+use crate::lwo2::tags::vertex_mapping::VertexMapKind;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// A bounding volume hierarchy over the triangulated FACE polygons of a layer, for ray casts and
+/// closest point queries in the layer's coordinates.
+#[derive(Debug, Clone)]
+pub struct Bvh<'a> {
+    triangles: Vec<Triangle<'a>>,
+    nodes: Vec<Node>,
+}
+
+/// A point on a polygon, found by [Bvh::ray_cast] or [Bvh::closest_point].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit<'a> {
+    /// For ray casts, the distance along the ray in multiples of the direction's length. For
+    /// closest point queries, the distance to the query point.
+    pub distance: f32,
+    pub point: [f32; 3],
+    /// The index of the polygon list in [LayerMesh::polygon_groups]
+    pub group: usize,
+    pub polygon: u32,
+    /// The points of the triangle of the polygon that was hit, in the polygon's vertex order
+    pub vert: [u32; 3],
+    /// The weight of each point of the triangle
+    pub barycentric: [f32; 3],
+    /// Whether the point is on the visible side of the polygon, from the ray's origin or the
+    /// query point
+    pub front_facing: bool,
+    /// The surface assigned through the polygon's SURF tag
+    pub surface: Option<&'a LwoString>,
+    /// The UV coordinates, if all points of the triangle are mapped
+    pub uv: Option<[f32; 2]>,
+}
+
+#[derive(Debug, Clone)]
+struct Triangle<'a> {
+    points: [Vec3; 3],
+    group: usize,
+    polygon: u32,
+    vert: [u32; 3],
+    surface: Option<&'a LwoString>,
+    uv: Option<[[f32; 2]; 3]>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    min: Vec3,
+    max: Vec3,
+    /// The indices of both children, or `None` for a leaf
+    children: Option<[usize; 2]>,
+    /// The triangles of a leaf
+    triangles: Range<usize>,
+}
+
+const LEAF_SIZE: usize = 4;
+
+impl<'a> LayerMesh<'a> {
+    /// Builds a [Bvh] over all FACE polygons, split into triangle fans. UVs are interpolated
+    /// from the TXUV map with the given name, or the first one if there is no name, with VMAD
+    /// values taking precedence over VMAP values. Polygons with fewer than three vertices or
+    /// that refer to missing points are left out.
+    pub fn bvh(&self, uv_map: Option<&str>) -> Bvh<'a> {
+        let points = &self.points.point_location;
+        let uv_map = match uv_map {
+            Some(name) => self.vertex_map(VertexMapKind::Uv, name),
+            None => self.vertex_maps_of(VertexMapKind::Uv).next(),
+        };
+        let uvs: HashMap<u32, [f32; 2]> = uv_map
+            .and_then(|map| map.map.as_uv())
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let mut triangles = vec![];
+        for (group_index, group) in self.polygon_groups.iter().enumerate() {
+            if group.polygons.kind != PolygonKind::Face {
+                continue;
+            }
+            let surfaces: HashMap<u32, &LwoString> = group
+                .tags_of(PolygonTagKind::Surface)
+                .and_then(|tags| tags.surfaces(self.tag_strings))
+                .into_iter()
+                .flatten()
+                .collect();
+            let discontinuous_uvs: HashMap<(u32, u32), [f32; 2]> = uv_map
+                .and_then(|map| group.discontinuous_map(VertexMapKind::Uv, &map.map.name))
+                .and_then(|map| map.map.as_uv())
+                .into_iter()
+                .flatten()
+                .map(|(vert, poly, uv)| ((vert, poly), uv))
+                .collect();
+
+            for (polygon, source) in group.polygons.polygons.iter().enumerate() {
+                let polygon = polygon as u32;
+                let vert = &source.vert;
+                if vert.len() < 3 || vert.iter().any(|&vert| vert as usize >= points.len()) {
+                    continue;
+                }
+                let uv = |vert: u32| {
+                    discontinuous_uvs
+                        .get(&(vert, polygon))
+                        .or_else(|| uvs.get(&vert))
+                        .copied()
+                };
+                for corner in 1..vert.len() - 1 {
+                    let vert = [vert[0], vert[corner], vert[corner + 1]];
+                    triangles.push(Triangle {
+                        points: vert.map(|vert| points[vert as usize]),
+                        group: group_index,
+                        polygon,
+                        vert,
+                        surface: surfaces.get(&polygon).copied(),
+                        uv: match vert.map(uv) {
+                            [Some(a), Some(b), Some(c)] => Some([a, b, c]),
+                            _ => None,
+                        },
+                    });
+                }
+            }
+        }
+
+        let mut bvh = Bvh {
+            triangles,
+            nodes: vec![],
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.build(0..bvh.triangles.len());
+        }
+        bvh
+    }
+}
+
+impl<'a> Bvh<'a> {
+    /// The nearest polygon along a ray, hitting polygons from both sides
+    pub fn ray_cast(&self, origin: [f32; 3], direction: [f32; 3]) -> Option<Hit<'a>> {
+        let inverse = direction.map(|value| 1.0 / value);
+        let mut best: Option<(usize, f32, [f32; 3])> = None;
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            let limit = best.map_or(f32::INFINITY, |(_, distance, _)| distance);
+            if !ray_hits_box(origin, inverse, node.min, node.max, limit) {
+                continue;
+            }
+            match node.children {
+                Some(children) => stack.extend(children),
+                None => {
+                    for index in node.triangles.clone() {
+                        if let Some((distance, barycentric)) =
+                            intersect(origin, direction, &self.triangles[index].points)
+                        {
+                            if distance < best.map_or(f32::INFINITY, |(_, best, _)| best) {
+                                best = Some((index, distance, barycentric));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let (index, distance, barycentric) = best?;
+        let triangle = &self.triangles[index];
+        let front_facing = dot(direction, normal(&triangle.points)) < 0.0;
+        Some(triangle.hit(distance, barycentric, front_facing))
+    }
+
+    /// The point on any polygon that is closest to `point`
+    pub fn closest_point(&self, point: [f32; 3]) -> Option<Hit<'a>> {
+        let mut best: Option<(usize, f32, [f32; 3])> = None;
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            let limit = best.map_or(f32::INFINITY, |(_, distance, _)| distance);
+            if box_distance_squared(point, node.min, node.max) > limit {
+                continue;
+            }
+            match node.children {
+                Some(children) => {
+                    // visit the nearer child first, so that the other one is more likely pruned
+                    let [near, far] = children;
+                    let distance = |child: usize| {
+                        box_distance_squared(point, self.nodes[child].min, self.nodes[child].max)
+                    };
+                    if distance(near) <= distance(far) {
+                        stack.extend([far, near]);
+                    } else {
+                        stack.extend([near, far]);
+                    }
+                }
+                None => {
+                    for index in node.triangles.clone() {
+                        let barycentric = closest_on_triangle(point, &self.triangles[index].points);
+                        let closest = interpolate(&self.triangles[index].points, barycentric);
+                        let offset = sub(point, closest);
+                        let distance = dot(offset, offset);
+                        if distance < best.map_or(f32::INFINITY, |(_, best, _)| best) {
+                            best = Some((index, distance, barycentric));
+                        }
+                    }
+                }
+            }
+        }
+        let (index, distance, barycentric) = best?;
+        let triangle = &self.triangles[index];
+        let closest = interpolate(&triangle.points, barycentric);
+        let front_facing = dot(sub(point, closest), normal(&triangle.points)) >= 0.0;
+        Some(triangle.hit(distance.sqrt(), barycentric, front_facing))
+    }
+
+    /// Adds the node for a range of triangles and its descendants, and returns its index
+    fn build(&mut self, range: Range<usize>) -> usize {
+        let (min, max) = bounds(
+            self.triangles[range.clone()]
+                .iter()
+                .flat_map(|triangle| triangle.points),
+        );
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            min,
+            max,
+            children: None,
+            triangles: range.clone(),
+        });
+        if range.len() <= LEAF_SIZE {
+            return index;
+        }
+
+        // split at the median center along the axis where the centers spread the most
+        let (center_min, center_max) =
+            bounds(self.triangles[range.clone()].iter().map(Triangle::center));
+        let extent = sub(center_max, center_min);
+        let axis = if extent[0] >= extent[1].max(extent[2]) {
+            0
+        } else if extent[1] >= extent[2] {
+            1
+        } else {
+            2
+        };
+        let middle = range.start + range.len() / 2;
+        self.triangles[range.clone()].select_nth_unstable_by(middle - range.start, |a, b| {
+            a.center()[axis].total_cmp(&b.center()[axis])
+        });
+        let left = self.build(range.start..middle);
+        let right = self.build(middle..range.end);
+        self.nodes[index].children = Some([left, right]);
+        index
+    }
+}
+
+impl<'a> Triangle<'a> {
+    fn center(&self) -> Vec3 {
+        scale(
+            add(add(self.points[0], self.points[1]), self.points[2]),
+            1.0 / 3.0,
+        )
+    }
+
+    fn hit(&self, distance: f32, barycentric: [f32; 3], front_facing: bool) -> Hit<'a> {
+        Hit {
+            distance,
+            point: interpolate(&self.points, barycentric),
+            group: self.group,
+            polygon: self.polygon,
+            vert: self.vert,
+            barycentric,
+            front_facing,
+            surface: self.surface,
+            uv: self.uv.map(|uv| {
+                [0, 1].map(|axis| {
+                    (0..3)
+                        .map(|corner| uv[corner][axis] * barycentric[corner])
+                        .sum()
+                })
+            }),
+        }
+    }
+}
+
+/// The normal as LightWave defines it, which faces the visible side
+fn normal([a, b, c]: &[Vec3; 3]) -> Vec3 {
+    cross(sub(*b, *a), sub(*c, *a))
+}
+
+fn interpolate(points: &[Vec3; 3], barycentric: [f32; 3]) -> Vec3 {
+    add(
+        add(
+            scale(points[0], barycentric[0]),
+            scale(points[1], barycentric[1]),
+        ),
+        scale(points[2], barycentric[2]),
+    )
+}
+
+fn bounds(points: impl Iterator<Item = Vec3>) -> (Vec3, Vec3) {
+    points.fold(
+        ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]),
+        |(min, max), point| {
+            (
+                [0, 1, 2].map(|axis| min[axis].min(point[axis])),
+                [0, 1, 2].map(|axis| max[axis].max(point[axis])),
+            )
+        },
+    )
+}
+
+/// The slab test, for boxes that the ray enters before `limit`
+fn ray_hits_box(origin: Vec3, inverse: Vec3, min: Vec3, max: Vec3, limit: f32) -> bool {
+    let mut near = 0.0f32;
+    let mut far = limit;
+    for axis in [0, 1, 2] {
+        if inverse[axis].is_infinite() {
+            // the ray is parallel to the slab
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return false;
+            }
+            continue;
+        }
+        let a = (min[axis] - origin[axis]) * inverse[axis];
+        let b = (max[axis] - origin[axis]) * inverse[axis];
+        near = near.max(a.min(b));
+        far = far.min(a.max(b));
+    }
+    near <= far
+}
+
+fn box_distance_squared(point: Vec3, min: Vec3, max: Vec3) -> f32 {
+    let offset = [0, 1, 2].map(|axis| {
+        (min[axis] - point[axis])
+            .max(point[axis] - max[axis])
+            .max(0.0)
+    });
+    dot(offset, offset)
+}
+
+/// The Möller-Trumbore intersection of a ray with a triangle from either side, as the distance
+/// along the ray and the barycentric coordinates of the intersection
+fn intersect(origin: Vec3, direction: Vec3, [a, b, c]: &[Vec3; 3]) -> Option<(f32, [f32; 3])> {
+    let edge1 = sub(*b, *a);
+    let edge2 = sub(*c, *a);
+    let p = cross(direction, edge2);
+    let determinant = dot(edge1, p);
+    if determinant == 0.0 {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let t = sub(origin, *a);
+    let u = dot(t, p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = cross(t, edge1);
+    let v = dot(direction, q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = dot(edge2, q) * inverse;
+    (distance >= 0.0).then_some((distance, [1.0 - u - v, u, v]))
+}
+
+/// The barycentric coordinates of the point on a triangle that is closest to `point`, following
+/// Christer Ericson's "Real-Time Collision Detection"
+fn closest_on_triangle(point: Vec3, [a, b, c]: &[Vec3; 3]) -> [f32; 3] {
+    let ab = sub(*b, *a);
+    let ac = sub(*c, *a);
+    let ap = sub(point, *a);
+    let d1 = dot(ab, ap);
+    let d2 = dot(ac, ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return [1.0, 0.0, 0.0];
+    }
+    let bp = sub(point, *b);
+    let d3 = dot(ab, bp);
+    let d4 = dot(ac, bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return [0.0, 1.0, 0.0];
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return [1.0 - v, v, 0.0];
+    }
+    let cp = sub(point, *c);
+    let d5 = dot(ab, cp);
+    let d6 = dot(ac, cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return [0.0, 0.0, 1.0];
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return [1.0 - w, 0.0, w];
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return [0.0, 1.0 - w, w];
+    }
+    let denominator = 1.0 / (va + vb + vc);
+    let v = vb * denominator;
+    let w = vc * denominator;
+    [1.0 - v - w, v, w]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::fixtures::{assert_close, cube_points, faces, layer_mesh, CUBE_FACES};
+    use crate::geometry::mesh::VertexMapRef;
+    use crate::lwo2::string::LwoString;
+    use crate::lwo2::tags::point_list::PointList;
+    use crate::lwo2::tags::vertex_mapping::{VertexMapKind, VertexMapping, VertexMappings};
+
+    #[test]
+    fn ray_cast_interpolates_uvs() {
+        // a 2x2 square, split into the triangles 0 1 2 and 0 2 3, with UVs half its positions
+        let points = PointList {
+            point_location: vec![
+                [0.0, 0.0, 0.0],
+                [2.0, 0.0, 0.0],
+                [2.0, 2.0, 0.0],
+                [0.0, 2.0, 0.0],
+            ],
+        };
+        let uvs = VertexMappings {
+            kind: VertexMapKind::Uv,
+            dimension: 2,
+            name: LwoString::new("UV"),
+            mapping: points
+                .point_location
+                .iter()
+                .enumerate()
+                .map(|(vert, point)| VertexMapping {
+                    vert: vert as u32,
+                    value: vec![point[0] / 2.0, point[1] / 2.0],
+                })
+                .collect(),
+        };
+        let groups = [faces(&[[0, 1, 2, 3]])];
+        let mut mesh = layer_mesh(&points, &groups);
+        mesh.vertex_maps.push(VertexMapRef {
+            map: &uvs,
+            parameter: None,
+        });
+        let bvh = mesh.bvh(Some("UV"));
+
+        let hit = bvh.ray_cast([1.5, 0.5, 3.0], [0.0, 0.0, -2.0]).unwrap();
+        // the distance is measured in multiples of the direction
+        assert!((hit.distance - 1.5).abs() < 1e-6);
+        assert_close(hit.point, [1.5, 0.5, 0.0]);
+        assert_eq!(hit.polygon, 0);
+        assert_eq!(hit.vert, [0, 1, 2]);
+        assert_close(hit.barycentric, [0.25, 0.5, 0.25]);
+        assert!(hit.front_facing);
+        let uv = hit.uv.unwrap();
+        assert_close([uv[0], uv[1], 0.0], [0.75, 0.25, 0.0]);
+
+        let hit = bvh.ray_cast([0.5, 1.5, -1.0], [0.0, 0.0, 1.0]).unwrap();
+        assert_eq!(hit.vert, [0, 2, 3]);
+        assert!(!hit.front_facing);
+
+        // parallel to the plane of the square, which is a flat slab of the bounding box
+        assert!(bvh.ray_cast([-1.0, 1.0, 0.0], [1.0, 0.0, 0.0]).is_none());
+        assert!(bvh.ray_cast([-1.0, 1.0, 1.0], [1.0, 0.0, 0.0]).is_none());
+    }
+
+    #[test]
+    fn ray_cast_parallel_to_slabs() {
+        let points = cube_points();
+        let groups = [faces(&CUBE_FACES)];
+        let bvh = layer_mesh(&points, &groups).bvh(None);
+
+        // only the Z slab is crossed, the ray stays within the X and Y slabs
+        let hit = bvh.ray_cast([0.25, 0.75, 3.0], [0.0, 0.0, -1.0]).unwrap();
+        assert!((hit.distance - 2.0).abs() < 1e-6);
+        assert_close(hit.point, [0.25, 0.75, 1.0]);
+        assert_eq!(hit.polygon, 1);
+        assert!(hit.front_facing);
+        assert!(hit.uv.is_none());
+
+        // from inside, the nearest face is seen from behind
+        let hit = bvh.ray_cast([0.5, 0.5, 0.5], [1.0, 0.0, 0.0]).unwrap();
+        assert!((hit.distance - 0.5).abs() < 1e-6);
+        assert_eq!(hit.polygon, 5);
+        assert!(!hit.front_facing);
+
+        // outside of the X slab
+        assert!(bvh.ray_cast([1.5, 0.5, 3.0], [0.0, 0.0, -1.0]).is_none());
+    }
+}
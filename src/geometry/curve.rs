@@ -0,0 +1,247 @@
+use crate::geometry::math::{add, distance, scale, sub};
+use crate::lwo2::tags::point_list::PointList;
+use crate::lwo2::tags::polygon_list::{PolygonKind, PolygonList, PolygonLists};
+
+/// The number of samples per segment used to approximate arc lengths
+const ARC_LENGTH_SAMPLES: usize = 32;
+
+/// A Catmull-Rom spline through the points of a CURV polygon.
+///
+/// Each pair of consecutive points is connected by a uniform Catmull-Rom segment, so the curve
+/// passes through every point. If a continuity flag is set, the outermost point on that side only
+/// controls the tangent and the curve starts (or ends) at its neighbour instead. Otherwise the
+/// missing neighbour of the end point is mirrored through it.
+///
+/// ```
+/// use lightwave_3d::geometry::curve::CatmullRomCurve;
+///
+/// let curve = CatmullRomCurve {
+///     points: vec![[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [2.0, 0.0, 0.0]],
+///     start_continuity: false,
+///     end_continuity: false,
+/// };
+/// assert_eq!(curve.segment_count(), 2);
+/// assert_eq!(curve.evaluate(0.5), Some([1.0, 1.0, 0.0]));
+/// assert_eq!(curve.tessellate(4).len(), 9);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatmullRomCurve {
+    pub points: Vec<[f32; 3]>,
+    pub start_continuity: bool,
+    pub end_continuity: bool,
+}
+
+impl CatmullRomCurve {
+    /// Looks up the points of a CURV polygon, or returns `None` if it refers to a point that
+    /// doesn't exist.
+    pub fn from_polygon(polygon: &PolygonList, points: &PointList) -> Option<Self> {
+        let flags = polygon.curve_flags();
+        Some(Self {
+            points: polygon
+                .vert
+                .iter()
+                .map(|&vert| points.point_location.get(vert as usize).copied())
+                .collect::<Option<_>>()?,
+            start_continuity: flags.start_continuity,
+            end_continuity: flags.end_continuity,
+        })
+    }
+
+    /// The index of the first point the curve passes through
+    fn first(&self) -> usize {
+        usize::from(self.start_continuity)
+    }
+
+    /// The index of the last point the curve passes through, if there is one
+    fn last(&self) -> Option<usize> {
+        self.points
+            .len()
+            .checked_sub(1 + usize::from(self.end_continuity))
+            .filter(|&last| last >= self.first())
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.last().map_or(0, |last| last - self.first())
+    }
+
+    /// The control point at `index`, mirroring the end points for indices outside of the curve
+    fn control_point(&self, index: isize) -> [f32; 3] {
+        let count = self.points.len() as isize;
+        if index < 0 {
+            mirror(self.points[0], self.points.get(1).copied())
+        } else if index >= count {
+            mirror(
+                self.points[count as usize - 1],
+                self.points.get(count as usize - 2).copied(),
+            )
+        } else {
+            self.points[index as usize]
+        }
+    }
+
+    fn segment_points(&self, segment: usize) -> [[f32; 3]; 4] {
+        let start = (self.first() + segment) as isize;
+        [
+            self.control_point(start - 1),
+            self.control_point(start),
+            self.control_point(start + 1),
+            self.control_point(start + 2),
+        ]
+    }
+
+    /// Maps a parameter of the whole curve to a segment and the parameter within it
+    fn locate(&self, t: f32) -> Option<(usize, f32)> {
+        let segments = self.segment_count();
+        if segments == 0 {
+            return None;
+        }
+        let u = t.clamp(0.0, 1.0) * segments as f32;
+        let segment = (u.floor() as usize).min(segments - 1);
+        Some((segment, u - segment as f32))
+    }
+
+    /// The position on a single segment, with `t` between 0 and 1
+    pub fn evaluate_segment(&self, segment: usize, t: f32) -> [f32; 3] {
+        let [p0, p1, p2, p3] = self.segment_points(segment);
+        let (t2, t3) = (t * t, t * t * t);
+        weighted(
+            [p0, p1, p2, p3],
+            [
+                0.5 * (-t3 + 2.0 * t2 - t),
+                0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+                0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+                0.5 * (t3 - t2),
+            ],
+        )
+    }
+
+    /// The derivative on a single segment, with `t` between 0 and 1
+    pub fn segment_tangent(&self, segment: usize, t: f32) -> [f32; 3] {
+        let [p0, p1, p2, p3] = self.segment_points(segment);
+        let t2 = t * t;
+        weighted(
+            [p0, p1, p2, p3],
+            [
+                0.5 * (-3.0 * t2 + 4.0 * t - 1.0),
+                0.5 * (9.0 * t2 - 10.0 * t),
+                0.5 * (-9.0 * t2 + 8.0 * t + 1.0),
+                0.5 * (3.0 * t2 - 2.0 * t),
+            ],
+        )
+    }
+
+    /// The position at `t`, where 0 is the start and 1 the end of the curve. Every segment
+    /// covers the same range of `t`. Returns `None` for curves without any segments.
+    pub fn evaluate(&self, t: f32) -> Option<[f32; 3]> {
+        self.locate(t)
+            .map(|(segment, t)| self.evaluate_segment(segment, t))
+    }
+
+    /// The (unnormalized) tangent at `t`, see [CatmullRomCurve::evaluate]
+    pub fn tangent(&self, t: f32) -> Option<[f32; 3]> {
+        self.locate(t)
+            .map(|(segment, t)| self.segment_tangent(segment, t))
+    }
+
+    /// Samples every segment `resolution` times, returning a polyline that includes all points
+    /// the curve passes through.
+    pub fn tessellate(&self, resolution: usize) -> Vec<[f32; 3]> {
+        let resolution = resolution.max(1);
+        let segments = self.segment_count();
+        let mut polyline: Vec<[f32; 3]> = (0..segments)
+            .flat_map(|segment| {
+                (0..resolution).map(move |step| (segment, step as f32 / resolution as f32))
+            })
+            .map(|(segment, t)| self.evaluate_segment(segment, t))
+            .collect();
+        if let Some(last) = self.last() {
+            polyline.push(self.points[last]);
+        }
+        polyline
+    }
+
+    /// The approximate length of the curve
+    pub fn length(&self) -> f32 {
+        self.tessellate(ARC_LENGTH_SAMPLES)
+            .windows(2)
+            .map(|pair| distance(pair[0], pair[1]))
+            .sum()
+    }
+
+    /// Samples the curve at (approximately) equal distances of `spacing` along the curve,
+    /// always including its end points. Non-positive spacings only return the end points.
+    pub fn tessellate_by_length(&self, spacing: f32) -> Vec<[f32; 3]> {
+        let dense = self.tessellate(ARC_LENGTH_SAMPLES);
+        let mut lengths = Vec::with_capacity(dense.len());
+        let mut total = 0.0;
+        for (index, point) in dense.iter().enumerate() {
+            if index > 0 {
+                total += distance(dense[index - 1], *point);
+            }
+            lengths.push(total);
+        }
+
+        let (first, last) = match (dense.first(), dense.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return vec![],
+        };
+        let count = if spacing > 0.0 {
+            (total / spacing).ceil() as usize
+        } else {
+            1
+        };
+        let step = 1.0 / (dense.len() - 1).max(1) as f32;
+        let mut polyline = vec![first];
+        let mut index = 0;
+        for sample in 1..count {
+            let target = sample as f32 * spacing;
+            while index + 2 < lengths.len() && lengths[index + 1] < target {
+                index += 1;
+            }
+            let span = lengths[index + 1] - lengths[index];
+            let fraction = if span > 0.0 {
+                (target - lengths[index]) / span
+            } else {
+                0.0
+            };
+            let t = (index as f32 + fraction) * step;
+            polyline.extend(self.evaluate(t));
+        }
+        if count > 0 && dense.len() > 1 {
+            polyline.push(last);
+        }
+        polyline
+    }
+}
+
+impl PolygonLists {
+    /// The curves of a CURV polygon list, or `None` for other polygon types. Curves that refer
+    /// to missing points are `None`.
+    pub fn curves<'a>(
+        &'a self,
+        points: &'a PointList,
+    ) -> Option<impl Iterator<Item = Option<CatmullRomCurve>> + 'a> {
+        (self.kind == PolygonKind::Curve).then(|| {
+            self.polygons
+                .iter()
+                .map(|polygon| CatmullRomCurve::from_polygon(polygon, points))
+        })
+    }
+}
+
+/// Reflects `neighbour` through `point`, so that the tangent at an end point faces its neighbour
+fn mirror(point: [f32; 3], neighbour: Option<[f32; 3]>) -> [f32; 3] {
+    match neighbour {
+        Some(neighbour) => sub(scale(point, 2.0), neighbour),
+        None => point,
+    }
+}
+
+fn weighted(points: [[f32; 3]; 4], weights: [f32; 4]) -> [f32; 3] {
+    points
+        .iter()
+        .zip(weights)
+        .fold([0.0; 3], |sum, (&point, weight)| {
+            add(sum, scale(point, weight))
+        })
+}
@@ -0,0 +1,65 @@
+//! Meshes shared by the tests of the geometry modules.
+
+use crate::geometry::math::Vec3;
+use crate::geometry::mesh::{LayerMesh, PolygonGroup};
+use crate::lwo2::tags::point_list::PointList;
+use crate::lwo2::tags::polygon_list::{PolygonKind, PolygonList, PolygonLists};
+
+/// The corners of the unit cube, with the index of each corner being `x + 2y + 4z`
+pub(crate) fn cube_points() -> PointList {
+    PointList {
+        point_location: (0..8)
+            .map(|index| [index & 1, index >> 1 & 1, index >> 2].map(|value| value as f32))
+            .collect(),
+    }
+}
+
+/// The six faces of the unit cube, facing outward
+pub(crate) const CUBE_FACES: [[u32; 4]; 6] = [
+    [0, 2, 3, 1],
+    [4, 5, 7, 6],
+    [0, 1, 5, 4],
+    [2, 6, 7, 3],
+    [0, 4, 6, 2],
+    [1, 3, 7, 5],
+];
+
+pub(crate) fn faces<P: AsRef<[u32]>>(faces: &[P]) -> PolygonLists {
+    PolygonLists {
+        kind: PolygonKind::Face,
+        polygons: faces
+            .iter()
+            .map(|vert| PolygonList {
+                flags: 0,
+                vert: vert.as_ref().to_vec(),
+            })
+            .collect(),
+    }
+}
+
+/// A mesh without a layer, vertex maps or polygon tags
+pub(crate) fn layer_mesh<'a>(points: &'a PointList, groups: &'a [PolygonLists]) -> LayerMesh<'a> {
+    LayerMesh {
+        layer: None,
+        points,
+        vertex_maps: vec![],
+        polygon_groups: groups
+            .iter()
+            .map(|polygons| PolygonGroup {
+                polygons,
+                tags: vec![],
+                discontinuous_maps: vec![],
+            })
+            .collect(),
+        tag_strings: &[],
+    }
+}
+
+pub(crate) fn assert_close(actual: Vec3, expected: Vec3) {
+    assert!(
+        (0..3).all(|axis| (actual[axis] - expected[axis]).abs() < 1e-5),
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
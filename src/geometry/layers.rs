@@ -0,0 +1,341 @@
+use crate::geometry::math::add;
+use crate::geometry::mesh::{LayerMesh, PolygonGroup, VertexMapRef};
+use crate::geometry::tree::{self, ParentIndex};
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::discontinuous_vertex_mapping::{
+    DiscontinuousVertexMapping, DiscontinuousVertexMappings,
+};
+use crate::lwo2::tags::layer::Layer;
+use crate::lwo2::tags::point_list::PointList;
+use crate::lwo2::tags::polygon_list::{PolygonList, PolygonLists};
+use crate::lwo2::tags::polygon_tag_mapping::{PolygonTagMapping, PolygonTagMappings};
+use crate::lwo2::tags::vertex_map_parameter::VertexMapParameter;
+use crate::lwo2::tags::vertex_mapping::{VertexMapKind, VertexMapping, VertexMappings};
+use crate::lwo2::tags::Tag;
+use crate::LightWaveObject;
+
+/// The LAYR chunks of an object, with their parents resolved.
+#[derive(Debug, Clone)]
+pub struct LayerTree<'a> {
+    pub nodes: Vec<LayerNode<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LayerNode<'a> {
+    pub layer: &'a Layer,
+    /// The index of the first layer with the parent's number
+    pub parent: Option<usize>,
+}
+
+impl LightWaveObject {
+    /// Resolves the parents of all layers. Parents that don't exist or that would make the
+    /// hierarchy circular are ignored.
+    pub fn layer_tree(&self) -> LayerTree<'_> {
+        let layers: Vec<&Layer> = self
+            .data
+            .iter()
+            .filter_map(|tag| match tag {
+                Tag::Layer(chunk) => Some(&chunk.data),
+                _ => None,
+            })
+            .collect();
+        let parents = tree::acyclic_parents(
+            layers
+                .iter()
+                .map(|layer| {
+                    let number = layer.parent_number()?;
+                    layers.iter().position(|layer| layer.number == number)
+                })
+                .collect(),
+        );
+        let nodes = layers
+            .into_iter()
+            .zip(parents)
+            .map(|(layer, parent)| LayerNode { layer, parent })
+            .collect();
+        LayerTree { nodes }
+    }
+}
+
+impl LayerTree<'_> {
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        tree::roots(&self.nodes)
+    }
+
+    pub fn children(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        tree::children(&self.nodes, node)
+    }
+
+    /// The first layer with the given number
+    pub fn find(&self, number: u16) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.layer.number == number)
+    }
+
+    /// All layers with the hidden flag set
+    pub fn hidden(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.nodes.len()).filter(|&node| self.nodes[node].layer.is_hidden())
+    }
+
+    /// All layers ordered so that parents come before their children
+    pub fn hierarchy_order(&self) -> Vec<usize> {
+        tree::hierarchy_order(&self.nodes)
+    }
+}
+
+impl ParentIndex for LayerNode<'_> {
+    fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+/// Owned geometry in the layout of a [LayerMesh], such as the result of flattening several
+/// layers into one.
+#[derive(Debug)]
+pub struct FlatMesh {
+    pub points: PointList,
+    pub vertex_maps: Vec<FlatVertexMap<VertexMappings>>,
+    pub polygon_groups: Vec<FlatPolygonGroup>,
+    pub tag_strings: Vec<LwoString>,
+}
+
+#[derive(Debug)]
+pub struct FlatPolygonGroup {
+    pub polygons: PolygonLists,
+    pub tags: Vec<PolygonTagMappings>,
+    pub discontinuous_maps: Vec<FlatVertexMap<DiscontinuousVertexMappings>>,
+}
+
+#[derive(Debug)]
+pub struct FlatVertexMap<T> {
+    pub map: T,
+    pub parameter: Option<VertexMapParameter>,
+}
+
+impl FlatMesh {
+    /// Borrows the geometry as a [LayerMesh] that doesn't belong to any layer
+    pub fn mesh(&self) -> LayerMesh<'_> {
+        LayerMesh {
+            layer: None,
+            points: &self.points,
+            vertex_maps: self.vertex_maps.iter().map(FlatVertexMap::as_ref).collect(),
+            polygon_groups: self
+                .polygon_groups
+                .iter()
+                .map(|group| PolygonGroup {
+                    polygons: &group.polygons,
+                    tags: group.tags.iter().collect(),
+                    discontinuous_maps: group
+                        .discontinuous_maps
+                        .iter()
+                        .map(FlatVertexMap::as_ref)
+                        .collect(),
+                })
+                .collect(),
+            tag_strings: &self.tag_strings,
+        }
+    }
+}
+
+impl<T> FlatVertexMap<T> {
+    fn as_ref(&self) -> VertexMapRef<'_, T> {
+        VertexMapRef {
+            map: &self.map,
+            parameter: self.parameter.as_ref(),
+        }
+    }
+}
+
+impl LightWaveObject {
+    /// Merges the geometry of all layers into one mesh in object space, optionally skipping
+    /// hidden layers.
+    ///
+    /// Points are moved by the pivot of their layer, since they are stored relative to it.
+    /// Polygon lists of the same kind are merged, as are vertex maps and polygon tags of the same
+    /// kind and name. Tag strings are merged by name, so polygons keep their surfaces and parts.
+    pub fn flatten(&self, include_hidden: bool) -> FlatMesh {
+        let mut flat = FlatMesh {
+            points: PointList {
+                point_location: vec![],
+            },
+            vertex_maps: vec![],
+            polygon_groups: vec![],
+            tag_strings: vec![],
+        };
+        for mesh in self.layer_meshes() {
+            if mesh.layer.is_some_and(|layer| layer.is_hidden()) && !include_hidden {
+                continue;
+            }
+            flat.append(&mesh);
+        }
+        flat
+    }
+}
+
+impl FlatMesh {
+    /// Appends the geometry of a layer, see [LightWaveObject::flatten]
+    fn append(&mut self, mesh: &LayerMesh) {
+        let pivot = mesh.layer.map_or([0.0; 3], |layer| layer.pivot);
+        let point_offset = self.points.point_location.len() as u32;
+        self.points.point_location.extend(
+            mesh.points
+                .point_location
+                .iter()
+                .map(|&point| add(point, pivot)),
+        );
+
+        for source in &mesh.vertex_maps {
+            let map = find_or_insert(&mut self.vertex_maps, source, |map| VertexMappings {
+                kind: map.kind,
+                dimension: map.dimension,
+                name: map.name.clone(),
+                mapping: vec![],
+            });
+            let is_absolute = source.map.kind == VertexMapKind::AbsoluteMorph;
+            map.mapping
+                .extend(source.map.mapping.iter().map(|mapping| VertexMapping {
+                    vert: mapping.vert + point_offset,
+                    value: if is_absolute && mapping.value.len() == 3 {
+                        add([0, 1, 2].map(|axis| mapping.value[axis]), pivot).to_vec()
+                    } else {
+                        mapping.value.clone()
+                    },
+                }));
+        }
+
+        for source in &mesh.polygon_groups {
+            let kind = source.polygons.kind;
+            let group = match self
+                .polygon_groups
+                .iter()
+                .position(|group| group.polygons.kind == kind)
+            {
+                Some(group) => &mut self.polygon_groups[group],
+                None => {
+                    self.polygon_groups.push(FlatPolygonGroup {
+                        polygons: PolygonLists {
+                            kind,
+                            polygons: vec![],
+                        },
+                        tags: vec![],
+                        discontinuous_maps: vec![],
+                    });
+                    self.polygon_groups.last_mut().unwrap()
+                }
+            };
+            let polygon_offset = group.polygons.polygons.len() as u32;
+            group
+                .polygons
+                .polygons
+                .extend(source.polygons.polygons.iter().map(|polygon| {
+                    PolygonList {
+                        flags: polygon.flags,
+                        vert: polygon
+                            .vert
+                            .iter()
+                            .map(|vert| vert + point_offset)
+                            .collect(),
+                    }
+                }));
+
+            for source in &source.tags {
+                let tags = match group.tags.iter().position(|tags| tags.kind == source.kind) {
+                    Some(tags) => &mut group.tags[tags],
+                    None => {
+                        group.tags.push(PolygonTagMappings {
+                            kind: source.kind,
+                            mappings: vec![],
+                        });
+                        group.tags.last_mut().unwrap()
+                    }
+                };
+                for mapping in &source.mappings {
+                    let tag = if source.kind.uses_tag_strings() {
+                        let Some(name) = mesh.tag_strings.get(mapping.tag as usize) else {
+                            continue;
+                        };
+                        let index = self.tag_strings.iter().position(|tag| tag == name);
+                        index.unwrap_or_else(|| {
+                            self.tag_strings.push(name.clone());
+                            self.tag_strings.len() - 1
+                        }) as u16
+                    } else {
+                        mapping.tag
+                    };
+                    tags.mappings.push(PolygonTagMapping {
+                        poly: mapping.poly + polygon_offset,
+                        tag,
+                    });
+                }
+            }
+
+            for source in &source.discontinuous_maps {
+                let map = find_or_insert(&mut group.discontinuous_maps, source, |map| {
+                    DiscontinuousVertexMappings {
+                        kind: map.kind,
+                        dimension: map.dimension,
+                        name: map.name.clone(),
+                        mappings: vec![],
+                    }
+                });
+                map.mappings
+                    .extend(
+                        source
+                            .map
+                            .mappings
+                            .iter()
+                            .map(|mapping| DiscontinuousVertexMapping {
+                                vert: mapping.vert + point_offset,
+                                poly: mapping.poly + polygon_offset,
+                                values: mapping.values.clone(),
+                            }),
+                    );
+            }
+        }
+    }
+}
+
+/// Vertex maps are identified by their kind and name, and the parameter of the first one wins
+trait NamedMap {
+    fn kind(&self) -> VertexMapKind;
+    fn name(&self) -> &LwoString;
+}
+
+impl NamedMap for VertexMappings {
+    fn kind(&self) -> VertexMapKind {
+        self.kind
+    }
+
+    fn name(&self) -> &LwoString {
+        &self.name
+    }
+}
+
+impl NamedMap for DiscontinuousVertexMappings {
+    fn kind(&self) -> VertexMapKind {
+        self.kind
+    }
+
+    fn name(&self) -> &LwoString {
+        &self.name
+    }
+}
+
+fn find_or_insert<'a, T: NamedMap>(
+    maps: &'a mut Vec<FlatVertexMap<T>>,
+    source: &VertexMapRef<T>,
+    empty: impl FnOnce(&T) -> T,
+) -> &'a mut T {
+    let index = maps
+        .iter()
+        .position(|map| map.map.kind() == source.map.kind() && map.map.name() == source.map.name());
+    let index = index.unwrap_or_else(|| {
+        maps.push(FlatVertexMap {
+            map: empty(source.map),
+            parameter: source.parameter.cloned(),
+        });
+        maps.len() - 1
+    });
+    &mut maps[index].map
+}
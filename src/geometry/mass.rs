@@ -0,0 +1,246 @@
+use crate::geometry::math::{add, area_vector, length, scale, Vec3};
+use crate::geometry::mesh::LayerMesh;
+use crate::geometry::topology::HalfEdgeMesh;
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::polygon_list::{PolygonKind, PolygonLists};
+use crate::lwo2::tags::polygon_tag_mapping::PolygonTagKind;
+use std::collections::HashMap;
+
+/// The area, volume and mass distribution of the FACE polygons of a layer, in the layer's
+/// coordinates. Mass properties assume a density of 1.
+#[derive(Debug, Clone)]
+pub struct MassProperties<'a> {
+    /// The total area of all polygons
+    pub area: f32,
+    /// The area of the polygons of every surface, in the order the surfaces first appear
+    pub surface_areas: Vec<SurfaceArea<'a>>,
+    /// Whether every edge is shared by exactly two consistently oriented polygons. Volume and
+    /// inertia are undefined for open meshes.
+    pub is_closed: bool,
+    /// The enclosed volume of a closed mesh. It is negative if the polygons face inward.
+    pub volume: Option<f32>,
+    /// The center of mass of the enclosed volume of a closed mesh, or the center of the surface
+    /// area of an open one
+    pub centroid: [f32; 3],
+    /// The inertia tensor of the enclosed volume of a closed mesh, around its centroid
+    pub inertia: Option<[[f32; 3]; 3]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceArea<'a> {
+    /// The surface name, or `None` for polygons without a SURF tag
+    pub surface: Option<&'a LwoString>,
+    pub area: f32,
+}
+
+impl<'a> LayerMesh<'a> {
+    /// Computes the [MassProperties] of all FACE polygons. Polygons are split into triangle fans,
+    /// which is exact for planar polygons. Polygons that refer to missing points are skipped.
+    pub fn mass_properties(&self) -> MassProperties<'a> {
+        let points = &self.points.point_location;
+        let mut faces = PolygonLists {
+            kind: PolygonKind::Face,
+            polygons: vec![],
+        };
+        let mut surface_areas: Vec<SurfaceArea> = vec![];
+        let mut area = 0.0;
+        let mut surface_centroid = [0.0; 3];
+        // the integrals of 1, x, y, z, x², y², z², xy, yz and zx over the enclosed volume
+        let mut integrals = [0.0f64; 10];
+
+        for group in self.polygon_groups_of(PolygonKind::Face) {
+            let surfaces: HashMap<u32, &LwoString> = group
+                .tags_of(PolygonTagKind::Surface)
+                .and_then(|tags| tags.surfaces(self.tag_strings))
+                .into_iter()
+                .flatten()
+                .collect();
+            for (index, polygon) in group.polygons.polygons.iter().enumerate() {
+                faces.polygons.push(polygon.clone());
+                let corners: Option<Vec<Vec3>> = polygon
+                    .vert
+                    .iter()
+                    .map(|&vert| points.get(vert as usize).copied())
+                    .collect();
+                let Some(corners) = corners.filter(|corners| corners.len() >= 3) else {
+                    continue;
+                };
+
+                let polygon_area = length(area_vector(&corners)) / 2.0;
+                area += polygon_area;
+                let surface = surfaces.get(&(index as u32)).copied();
+                match surface_areas
+                    .iter_mut()
+                    .find(|entry| entry.surface == surface)
+                {
+                    Some(entry) => entry.area += polygon_area,
+                    None => surface_areas.push(SurfaceArea {
+                        surface,
+                        area: polygon_area,
+                    }),
+                }
+
+                for corner in 1..corners.len() - 1 {
+                    let triangle = [corners[0], corners[corner], corners[corner + 1]];
+                    let triangle_area = length(area_vector(&triangle)) / 2.0;
+                    for point in triangle {
+                        surface_centroid = add(surface_centroid, scale(point, triangle_area / 3.0));
+                    }
+                    add_triangle_integrals(&mut integrals, triangle);
+                }
+            }
+        }
+
+        let is_closed =
+            HalfEdgeMesh::new(&faces, self.points).is_some_and(|topology| topology.is_closed());
+        let surface_centroid = if area > 0.0 {
+            surface_centroid.map(|sum| sum / area)
+        } else {
+            [0.0; 3]
+        };
+        let (volume, centroid, inertia) = if is_closed {
+            volume_properties(integrals).map_or(
+                (Some(0.0), surface_centroid, None),
+                |(volume, centroid, inertia)| (Some(volume), centroid, Some(inertia)),
+            )
+        } else {
+            (None, surface_centroid, None)
+        };
+
+        MassProperties {
+            area,
+            surface_areas,
+            is_closed,
+            volume,
+            centroid,
+            inertia,
+        }
+    }
+}
+
+/// Adds the volume integrals of the tetrahedron between the origin and a triangle, following
+/// David Eberly's "Polyhedral Mass Properties". The triangle's normal, following LightWave's
+/// vertex order, has to face out of the volume.
+fn add_triangle_integrals(integrals: &mut [f64; 10], triangle: [Vec3; 3]) {
+    let [p0, p1, p2] = triangle.map(|point| point.map(f64::from));
+    let e1 = [0, 1, 2].map(|axis| p1[axis] - p0[axis]);
+    let e2 = [0, 1, 2].map(|axis| p2[axis] - p0[axis]);
+    let d = [
+        e1[1] * e2[2] - e1[2] * e2[1],
+        e1[2] * e2[0] - e1[0] * e2[2],
+        e1[0] * e2[1] - e1[1] * e2[0],
+    ];
+    let [x, y, z] = [0, 1, 2].map(|axis| subexpressions(p0[axis], p1[axis], p2[axis]));
+    integrals[0] += d[0] * x.f1;
+    integrals[1] += d[0] * x.f2;
+    integrals[2] += d[1] * y.f2;
+    integrals[3] += d[2] * z.f2;
+    integrals[4] += d[0] * x.f3;
+    integrals[5] += d[1] * y.f3;
+    integrals[6] += d[2] * z.f3;
+    integrals[7] += d[0] * (p0[1] * x.g[0] + p1[1] * x.g[1] + p2[1] * x.g[2]);
+    integrals[8] += d[1] * (p0[2] * y.g[0] + p1[2] * y.g[1] + p2[2] * y.g[2]);
+    integrals[9] += d[2] * (p0[0] * z.g[0] + p1[0] * z.g[1] + p2[0] * z.g[2]);
+}
+
+struct Subexpressions {
+    f1: f64,
+    f2: f64,
+    f3: f64,
+    g: [f64; 3],
+}
+
+fn subexpressions(w0: f64, w1: f64, w2: f64) -> Subexpressions {
+    let temp0 = w0 + w1;
+    let f1 = temp0 + w2;
+    let temp1 = w0 * w0;
+    let temp2 = temp1 + w1 * temp0;
+    let f2 = temp2 + w2 * f1;
+    let f3 = w0 * temp1 + w1 * temp2 + w2 * f2;
+    Subexpressions {
+        f1,
+        f2,
+        f3,
+        g: [w0, w1, w2].map(|w| f2 + w * (f1 + w)),
+    }
+}
+
+/// The volume, centroid and inertia tensor around the centroid from the summed integrals, or
+/// `None` if there is no volume
+fn volume_properties(integrals: [f64; 10]) -> Option<(f32, Vec3, [[f32; 3]; 3])> {
+    const FACTORS: [f64; 10] = [
+        1.0 / 6.0,
+        1.0 / 24.0,
+        1.0 / 24.0,
+        1.0 / 24.0,
+        1.0 / 60.0,
+        1.0 / 60.0,
+        1.0 / 60.0,
+        1.0 / 120.0,
+        1.0 / 120.0,
+        1.0 / 120.0,
+    ];
+    let [volume, x, y, z, xx, yy, zz, xy, yz, zx] =
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9].map(|index| integrals[index] * FACTORS[index]);
+    if volume.abs() < f64::EPSILON {
+        return None;
+    }
+    let [cx, cy, cz] = [x / volume, y / volume, z / volume];
+    // inward facing polygons negate all integrals, so the inertia is computed from positive ones
+    let sign = volume.signum();
+    let mass = volume.abs();
+    let [xx, yy, zz, xy, yz, zx] = [xx, yy, zz, xy, yz, zx].map(|value| value * sign);
+    let ixx = yy + zz - mass * (cy * cy + cz * cz);
+    let iyy = zz + xx - mass * (cz * cz + cx * cx);
+    let izz = xx + yy - mass * (cx * cx + cy * cy);
+    let ixy = -(xy - mass * cx * cy);
+    let iyz = -(yz - mass * cy * cz);
+    let izx = -(zx - mass * cz * cx);
+    Some((
+        volume as f32,
+        [cx, cy, cz].map(|value| value as f32),
+        [[ixx, ixy, izx], [ixy, iyy, iyz], [izx, iyz, izz]]
+            .map(|row| row.map(|value| value as f32)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::fixtures::{assert_close, cube_points, faces, layer_mesh, CUBE_FACES};
+
+    #[test]
+    fn unit_cube() {
+        let points = cube_points();
+        let groups = [faces(&CUBE_FACES)];
+        let properties = layer_mesh(&points, &groups).mass_properties();
+
+        assert!(properties.is_closed);
+        assert!((properties.area - 6.0).abs() < 1e-5);
+        assert!((properties.volume.unwrap() - 1.0).abs() < 1e-5);
+        assert_close(properties.centroid, [0.5; 3]);
+        let inertia = properties.inertia.unwrap();
+        for (axis, row) in inertia.into_iter().enumerate() {
+            let mut expected = [0.0; 3];
+            expected[axis] = 1.0 / 6.0;
+            assert_close(row, expected);
+        }
+    }
+
+    #[test]
+    fn inside_out_cube() {
+        let points = cube_points();
+        let flipped: Vec<[u32; 4]> = CUBE_FACES
+            .into_iter()
+            .map(|mut face| {
+                face.reverse();
+                face
+            })
+            .collect();
+        let groups = [faces(&flipped)];
+        let properties = layer_mesh(&points, &groups).mass_properties();
+
+        assert!(properties.is_closed);
+        assert!((properties.volume.unwrap() + 1.0).abs() < 1e-5);
+        assert_close(properties.centroid, [0.5; 3]);
+    }
+}
@@ -0,0 +1,137 @@
+//! Small vector helpers for the `[f32; 3]` points used throughout the format.
+
+pub(crate) type Vec3 = [f32; 3];
+
+pub(crate) fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(a: Vec3, factor: f32) -> Vec3 {
+    [a[0] * factor, a[1] * factor, a[2] * factor]
+}
+
+pub(crate) fn length(a: Vec3) -> f32 {
+    dot(a, a).sqrt()
+}
+
+pub(crate) fn distance(a: Vec3, b: Vec3) -> f32 {
+    length(sub(a, b))
+}
+
+pub(crate) fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// The (unnormalized) normal of a polygon as LightWave defines it, the cross product of its first
+/// and last edges, which faces the side from which the vertices appear clockwise.
+pub(crate) fn polygon_normal(points: &[Vec3]) -> Vec3 {
+    match points {
+        [first, second, .., last] => cross(sub(*second, *first), sub(*last, *first)),
+        _ => [0.0; 3],
+    }
+}
+
+/// The sum of the cross products of consecutive vertices, whose length is twice the area of a
+/// planar polygon. It points the same way as [polygon_normal], but takes every vertex into account.
+pub(crate) fn area_vector(points: &[Vec3]) -> Vec3 {
+    let mut sum = [0.0; 3];
+    for (index, &point) in points.iter().enumerate() {
+        sum = add(sum, cross(point, points[(index + 1) % points.len()]));
+    }
+    sum
+}
+
+/// Scales `a` to unit length, or returns `None` if it has no length
+pub(crate) fn normalize(a: Vec3) -> Option<Vec3> {
+    let length = length(a);
+    (length > f32::EPSILON).then(|| scale(a, 1.0 / length))
+}
+
+/// A row-major affine transform applied to column vectors, with the translation in the last
+/// column
+pub(crate) type Mat4 = [[f32; 4]; 4];
+
+pub(crate) const IDENTITY: Mat4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+pub(crate) fn multiply(a: &Mat4, b: &Mat4) -> Mat4 {
+    let mut result = [[0.0; 4]; 4];
+    for (row, result) in result.iter_mut().enumerate() {
+        for (column, result) in result.iter_mut().enumerate() {
+            *result = (0..4).map(|i| a[row][i] * b[i][column]).sum();
+        }
+    }
+    result
+}
+
+pub(crate) fn transform_point(m: &Mat4, p: Vec3) -> Vec3 {
+    [0, 1, 2].map(|row| m[row][0] * p[0] + m[row][1] * p[1] + m[row][2] * p[2] + m[row][3])
+}
+
+pub(crate) fn translation(offset: Vec3) -> Mat4 {
+    let mut m = IDENTITY;
+    for (row, offset) in offset.into_iter().enumerate() {
+        m[row][3] = offset;
+    }
+    m
+}
+
+/// Embeds a 3x3 matrix, given as rows, into an affine transform
+pub(crate) fn linear(rows: [Vec3; 3]) -> Mat4 {
+    let mut m = IDENTITY;
+    for (row, values) in rows.into_iter().enumerate() {
+        m[row][..3].copy_from_slice(&values);
+    }
+    m
+}
+
+pub(crate) fn transpose3(m: [Vec3; 3]) -> [Vec3; 3] {
+    [0, 1, 2].map(|row| [m[0][row], m[1][row], m[2][row]])
+}
+
+pub(crate) fn multiply3(a: [Vec3; 3], b: [Vec3; 3]) -> [Vec3; 3] {
+    let b = transpose3(b);
+    a.map(|row| b.map(|column| dot(row, column)))
+}
+
+/// The 3x3 linear part of an affine transform
+pub(crate) fn linear_part(m: &Mat4) -> [Vec3; 3] {
+    [0, 1, 2].map(|row| [m[row][0], m[row][1], m[row][2]])
+}
+
+pub(crate) fn apply3(m: [Vec3; 3], v: Vec3) -> Vec3 {
+    m.map(|row| dot(row, v))
+}
+
+pub(crate) fn determinant3(m: [Vec3; 3]) -> f32 {
+    dot(m[0], cross(m[1], m[2]))
+}
+
+/// The inverse of a 3x3 matrix, or `None` if it is singular
+pub(crate) fn inverse3(m: [Vec3; 3]) -> Option<[Vec3; 3]> {
+    let determinant = determinant3(m);
+    if determinant.abs() <= f32::EPSILON {
+        return None;
+    }
+    // the columns of the inverse are the cross products of the rows, scaled by the determinant
+    let columns = [cross(m[1], m[2]), cross(m[2], m[0]), cross(m[0], m[1])];
+    Some(transpose3(
+        columns.map(|column| scale(column, 1.0 / determinant)),
+    ))
+}
@@ -0,0 +1,205 @@
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::discontinuous_vertex_mapping::DiscontinuousVertexMappings;
+use crate::lwo2::tags::layer::Layer;
+use crate::lwo2::tags::point_list::PointList;
+use crate::lwo2::tags::polygon_list::{PolygonKind, PolygonLists};
+use crate::lwo2::tags::polygon_tag_mapping::{PolygonTagKind, PolygonTagMappings};
+use crate::lwo2::tags::vertex_map_parameter::VertexMapParameter;
+use crate::lwo2::tags::vertex_mapping::{VertexMapKind, VertexMappings};
+use crate::lwo2::tags::Tag;
+use crate::LightWaveObject;
+
+/// The geometry chunks that belong to one PNTS chunk, grouped the way they refer to each other.
+///
+/// VMAPs refer to the most recent PNTS, while PTAGs and VMADs refer to the most recent POLS and
+/// are therefore grouped with it. A VMPA describes the VMAP or VMAD right before it.
+#[derive(Debug, Clone)]
+pub struct LayerMesh<'a> {
+    /// The layer the points belong to, or `None` for data before the first LAYR
+    pub layer: Option<&'a Layer>,
+    pub points: &'a PointList,
+    pub vertex_maps: Vec<VertexMapRef<'a, VertexMappings>>,
+    pub polygon_groups: Vec<PolygonGroup<'a>>,
+    /// The TAGS the polygon tags of this mesh refer to
+    pub tag_strings: &'a [LwoString],
+}
+
+/// A standalone list of points and FACE polygons, as produced by operations that generate new
+/// geometry.
+#[derive(Debug)]
+pub struct FaceMesh {
+    pub points: PointList,
+    pub polygons: PolygonLists,
+}
+
+/// A POLS chunk along with the PTAGs and VMADs that refer to it.
+#[derive(Debug, Clone)]
+pub struct PolygonGroup<'a> {
+    pub polygons: &'a PolygonLists,
+    pub tags: Vec<&'a PolygonTagMappings>,
+    pub discontinuous_maps: Vec<VertexMapRef<'a, DiscontinuousVertexMappings>>,
+}
+
+/// A VMAP or VMAD, along with the VMPA that describes it.
+#[derive(Debug)]
+pub struct VertexMapRef<'a, T> {
+    pub map: &'a T,
+    pub parameter: Option<&'a VertexMapParameter>,
+}
+
+impl<T> Clone for VertexMapRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for VertexMapRef<'_, T> {}
+
+impl LightWaveObject {
+    /// Groups the geometry chunks of all layers, in file order. Chunks that precede the PNTS
+    /// (or POLS) they would refer to are skipped.
+    pub fn layer_meshes(&self) -> Vec<LayerMesh<'_>> {
+        let mut meshes: Vec<LayerMesh> = vec![];
+        let mut layer = None;
+        let mut tag_strings: &[LwoString] = &[];
+        // whether the mesh at the end of `meshes` belongs to the current layer
+        let mut in_mesh = false;
+        // whether the previous chunk was a VMAD (true) or VMAP (false), which a VMPA refers to
+        let mut previous_map = None;
+
+        for tag in &self.data {
+            let is_discontinuous = previous_map.take();
+            let mesh = match tag {
+                Tag::Layer(chunk) => {
+                    layer = Some(&chunk.data);
+                    in_mesh = false;
+                    continue;
+                }
+                Tag::TagStrings(chunk) => {
+                    tag_strings = &chunk.tag_strings;
+                    continue;
+                }
+                Tag::PointList(chunk) => {
+                    meshes.push(LayerMesh {
+                        layer,
+                        points: &chunk.data,
+                        vertex_maps: vec![],
+                        polygon_groups: vec![],
+                        tag_strings,
+                    });
+                    in_mesh = true;
+                    continue;
+                }
+                _ => match meshes.last_mut() {
+                    Some(mesh) if in_mesh => mesh,
+                    _ => continue,
+                },
+            };
+
+            match tag {
+                Tag::VertexMapping(chunk) => {
+                    mesh.vertex_maps.push(VertexMapRef {
+                        map: &chunk.data,
+                        parameter: None,
+                    });
+                    previous_map = Some(false);
+                }
+                Tag::PolygonList(chunk) => mesh.polygon_groups.push(PolygonGroup {
+                    polygons: &chunk.data,
+                    tags: vec![],
+                    discontinuous_maps: vec![],
+                }),
+                Tag::PolygonTagMapping(chunk) => {
+                    if let Some(group) = mesh.polygon_groups.last_mut() {
+                        group.tags.push(&chunk.data);
+                        mesh.tag_strings = tag_strings;
+                    }
+                }
+                Tag::DiscontinuousVertexMapping(chunk) => {
+                    if let Some(group) = mesh.polygon_groups.last_mut() {
+                        group.discontinuous_maps.push(VertexMapRef {
+                            map: &chunk.data,
+                            parameter: None,
+                        });
+                        previous_map = Some(true);
+                    }
+                }
+                Tag::VertexMapParameter(chunk) => {
+                    let parameter = Some(&chunk.data);
+                    match is_discontinuous {
+                        Some(false) => {
+                            if let Some(map) = mesh.vertex_maps.last_mut() {
+                                map.parameter = parameter;
+                            }
+                        }
+                        Some(true) => {
+                            let group = mesh.polygon_groups.last_mut();
+                            if let Some(map) =
+                                group.and_then(|group| group.discontinuous_maps.last_mut())
+                            {
+                                map.parameter = parameter;
+                            }
+                        }
+                        None => (),
+                    }
+                }
+                _ => (),
+            }
+        }
+        meshes
+    }
+}
+
+impl<'a> LayerMesh<'a> {
+    /// The first VMAP with the given kind and name
+    pub fn vertex_map(
+        &self,
+        kind: VertexMapKind,
+        name: &str,
+    ) -> Option<VertexMapRef<'a, VertexMappings>> {
+        self.vertex_maps
+            .iter()
+            .find(|map| map.map.kind == kind && map.map.name == name)
+            .copied()
+    }
+
+    /// All VMAPs of the given kind
+    pub fn vertex_maps_of(
+        &self,
+        kind: VertexMapKind,
+    ) -> impl Iterator<Item = VertexMapRef<'a, VertexMappings>> + '_ {
+        self.vertex_maps
+            .iter()
+            .filter(move |map| map.map.kind == kind)
+            .copied()
+    }
+
+    /// All polygon lists of the given kind
+    pub fn polygon_groups_of(
+        &self,
+        kind: PolygonKind,
+    ) -> impl Iterator<Item = &PolygonGroup<'a>> + '_ {
+        self.polygon_groups
+            .iter()
+            .filter(move |group| group.polygons.kind == kind)
+    }
+}
+
+impl<'a> PolygonGroup<'a> {
+    /// The first PTAG of the given kind
+    pub fn tags_of(&self, kind: PolygonTagKind) -> Option<&'a PolygonTagMappings> {
+        self.tags.iter().find(|tags| tags.kind == kind).copied()
+    }
+
+    /// The first VMAD with the given kind and name
+    pub fn discontinuous_map(
+        &self,
+        kind: VertexMapKind,
+        name: &str,
+    ) -> Option<VertexMapRef<'a, DiscontinuousVertexMappings>> {
+        self.discontinuous_maps
+            .iter()
+            .find(|map| map.map.kind == kind && map.map.name == name)
+            .copied()
+    }
+}
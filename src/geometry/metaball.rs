@@ -0,0 +1,263 @@
+use crate::geometry::math::{add, distance, dot, polygon_normal, scale, sub};
+use crate::geometry::mesh::{FaceMesh, LayerMesh, PolygonGroup};
+use crate::lwo2::tags::point_list::PointList;
+use crate::lwo2::tags::polygon_list::{PolygonKind, PolygonList, PolygonLists};
+use crate::lwo2::tags::vertex_mapping::VertexMapKind;
+use std::collections::HashMap;
+
+/// A single metaball, the point of an MBAL polygon and its radius of influence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metaball {
+    pub center: [f32; 3],
+    pub radius: f32,
+}
+
+impl Metaball {
+    /// The influence at `point`, which falls off smoothly from 1 at the center to 0 at the
+    /// radius as `(1 - r²/R²)²`.
+    pub fn influence(&self, point: [f32; 3]) -> f32 {
+        let ratio = sub(point, self.center);
+        let ratio = dot(ratio, ratio) / (self.radius * self.radius);
+        if ratio < 1.0 {
+            (1.0 - ratio) * (1.0 - ratio)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MetaballOptions {
+    /// The number of grid cells along the longest side of the bounding box
+    pub resolution: usize,
+    /// The summed influence at which the surface lies
+    pub threshold: f32,
+}
+
+impl Default for MetaballOptions {
+    fn default() -> Self {
+        Self {
+            resolution: 32,
+            threshold: 0.5,
+        }
+    }
+}
+
+impl LayerMesh<'_> {
+    /// The metaballs of an MBAL polygon list, with their radii taken from the first MBAL map.
+    /// Points without a radius are skipped.
+    pub fn metaballs(&self, group: &PolygonGroup) -> Vec<Metaball> {
+        let radii: HashMap<u32, f32> = self
+            .vertex_maps_of(VertexMapKind::Metaball)
+            .next()
+            .map(|map| {
+                map.map
+                    .mapping
+                    .iter()
+                    .filter_map(|mapping| Some((mapping.vert, *mapping.value.first()?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        group
+            .polygons
+            .polygons
+            .iter()
+            .filter_map(|polygon| {
+                let &vert = polygon.vert.first()?;
+                Some(Metaball {
+                    center: *self.points.point_location.get(vert as usize)?,
+                    radius: *radii.get(&vert).filter(|&&radius| radius > 0.0)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The summed influence of all metaballs at `point`
+pub fn field(metaballs: &[Metaball], point: [f32; 3]) -> f32 {
+    metaballs.iter().map(|ball| ball.influence(point)).sum()
+}
+
+/// Builds a triangle mesh of the isosurface where the summed influence of the metaballs equals
+/// the threshold.
+///
+/// The field is sampled on a regular grid and contoured with surface nets, a simple form of dual
+/// contouring: every cell the surface passes through gets one vertex at the mean of the
+/// crossings on its edges, and the cells around every crossed grid edge are connected. The
+/// triangles face away from the metaballs.
+pub fn polygonize(metaballs: &[Metaball], options: &MetaballOptions) -> FaceMesh {
+    let mut mesh = FaceMesh {
+        points: PointList {
+            point_location: vec![],
+        },
+        polygons: PolygonLists {
+            kind: PolygonKind::Face,
+            polygons: vec![],
+        },
+    };
+    let Some(grid) = Grid::new(metaballs, options.resolution) else {
+        return mesh;
+    };
+    let values: Vec<f32> = (0..grid.point_count())
+        .map(|index| field(metaballs, grid.position(grid.coordinates(index))) - options.threshold)
+        .collect();
+    let value = |[x, y, z]: [usize; 3]| values[grid.index([x, y, z])];
+
+    // one vertex for every cell with corners on both sides of the surface
+    let mut cell_vertices = HashMap::new();
+    for x in 0..grid.size[0] {
+        for y in 0..grid.size[1] {
+            for z in 0..grid.size[2] {
+                let mut crossings = vec![];
+                for [a, b] in CELL_EDGES {
+                    let a = [x + a[0], y + a[1], z + a[2]];
+                    let b = [x + b[0], y + b[1], z + b[2]];
+                    let (value_a, value_b) = (value(a), value(b));
+                    if (value_a > 0.0) != (value_b > 0.0) {
+                        let t = value_a / (value_a - value_b);
+                        let (a, b) = (grid.position(a), grid.position(b));
+                        crossings.push(add(a, scale(sub(b, a), t)));
+                    }
+                }
+                if !crossings.is_empty() {
+                    let sum = crossings
+                        .iter()
+                        .fold([0.0; 3], |sum, &point| add(sum, point));
+                    mesh.points
+                        .point_location
+                        .push(scale(sum, 1.0 / crossings.len() as f32));
+                    cell_vertices.insert([x, y, z], mesh.points.point_location.len() as u32 - 1);
+                }
+            }
+        }
+    }
+
+    // a quad between the four cells around every grid edge that crosses the surface
+    for start in 0..grid.point_count() {
+        let start = grid.coordinates(start);
+        for axis in 0..3 {
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+            let mut end = start;
+            end[axis] += 1;
+            if end[axis] > grid.size[axis] || start[u] == 0 || start[v] == 0 {
+                continue;
+            }
+            let inside = value(start) > 0.0;
+            if inside == (value(end) > 0.0) {
+                continue;
+            }
+            let cell = |du: usize, dv: usize| {
+                let mut cell = start;
+                cell[u] -= 1 - du;
+                cell[v] -= 1 - dv;
+                cell
+            };
+            let cells = [cell(0, 0), cell(1, 0), cell(1, 1), cell(0, 1)];
+            let Some(mut quad) = cells
+                .iter()
+                .map(|cell| cell_vertices.get(cell).copied())
+                .collect::<Option<Vec<u32>>>()
+            else {
+                continue;
+            };
+
+            // face from the inside towards the outside
+            let centers: Vec<[f32; 3]> = cells.iter().map(|&cell| grid.position(cell)).collect();
+            let outwards = sub(grid.position(end), grid.position(start));
+            let outwards = if inside {
+                outwards
+            } else {
+                scale(outwards, -1.0)
+            };
+            if dot(polygon_normal(&centers), outwards) < 0.0 {
+                quad.reverse();
+            }
+            let points = &mesh.points.point_location;
+            let point = |index: u32| points[index as usize];
+            let triangles = if distance(point(quad[0]), point(quad[2]))
+                <= distance(point(quad[1]), point(quad[3]))
+            {
+                [[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]]
+            } else {
+                [[quad[0], quad[1], quad[3]], [quad[1], quad[2], quad[3]]]
+            };
+            mesh.polygons
+                .polygons
+                .extend(triangles.into_iter().map(|vert| PolygonList {
+                    flags: 0,
+                    vert: vert.to_vec(),
+                }));
+        }
+    }
+    mesh
+}
+
+/// The corner offsets of the 12 edges of a cell
+const CELL_EDGES: [[[usize; 3]; 2]; 12] = [
+    [[0, 0, 0], [1, 0, 0]],
+    [[0, 1, 0], [1, 1, 0]],
+    [[0, 0, 1], [1, 0, 1]],
+    [[0, 1, 1], [1, 1, 1]],
+    [[0, 0, 0], [0, 1, 0]],
+    [[1, 0, 0], [1, 1, 0]],
+    [[0, 0, 1], [0, 1, 1]],
+    [[1, 0, 1], [1, 1, 1]],
+    [[0, 0, 0], [0, 0, 1]],
+    [[1, 0, 0], [1, 0, 1]],
+    [[0, 1, 0], [0, 1, 1]],
+    [[1, 1, 0], [1, 1, 1]],
+];
+
+/// A regular grid of cells around all metaballs, with one empty cell of padding on every side
+struct Grid {
+    origin: [f32; 3],
+    cell_size: f32,
+    /// The number of cells along each axis
+    size: [usize; 3],
+}
+
+impl Grid {
+    fn new(metaballs: &[Metaball], resolution: usize) -> Option<Self> {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for ball in metaballs {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(ball.center[axis] - ball.radius);
+                max[axis] = max[axis].max(ball.center[axis] + ball.radius);
+            }
+        }
+        let extent = sub(max, min);
+        let longest = extent[0].max(extent[1]).max(extent[2]);
+        if !(longest.is_finite() && longest > 0.0) {
+            return None;
+        }
+        let cell_size = longest / resolution.max(1) as f32;
+        Some(Self {
+            origin: sub(min, [cell_size; 3]),
+            cell_size,
+            size: extent.map(|extent| (extent / cell_size).ceil() as usize + 2),
+        })
+    }
+
+    fn point_count(&self) -> usize {
+        self.size.iter().map(|size| size + 1).product()
+    }
+
+    fn index(&self, [x, y, z]: [usize; 3]) -> usize {
+        (x * (self.size[1] + 1) + y) * (self.size[2] + 1) + z
+    }
+
+    fn coordinates(&self, index: usize) -> [usize; 3] {
+        let z = index % (self.size[2] + 1);
+        let index = index / (self.size[2] + 1);
+        [index / (self.size[1] + 1), index % (self.size[1] + 1), z]
+    }
+
+    /// The position of a grid point, or of the lower corner of a cell
+    fn position(&self, coordinates: [usize; 3]) -> [f32; 3] {
+        add(
+            self.origin,
+            coordinates.map(|coordinate| coordinate as f32 * self.cell_size),
+        )
+    }
+}
@@ -0,0 +1,19 @@
+//! Geometry derived from the parsed chunks, such as evaluated curves and surfaces.
+
+pub mod bounds;
+pub mod bvh;
+pub mod curve;
+#[cfg(test)]
+mod fixtures;
+pub mod layers;
+pub mod mass;
+pub(crate) mod math;
+pub mod mesh;
+pub mod metaball;
+pub mod morph;
+pub mod selection;
+pub mod skeleton;
+pub mod skinning;
+pub mod subdivision;
+pub mod topology;
+pub(crate) mod tree;
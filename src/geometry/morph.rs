@@ -0,0 +1,211 @@
+use crate::geometry::math::{add, scale, sub};
+use crate::geometry::mesh::LayerMesh;
+use crate::lwo2::tags::vertex_mapping::{VertexMapKind, VertexMappings};
+
+/// A morph target, stored either as displacements (MORF) or absolute positions (SPOT).
+///
+/// By convention, morph names of the form `Group.Name` are grouped, which is how LightWave
+/// organizes endomorphs in its morph mixer.
+#[derive(Debug, Clone, Copy)]
+pub struct MorphTarget<'a> {
+    pub map: &'a VertexMappings,
+}
+
+impl<'a> MorphTarget<'a> {
+    /// The full name of the map
+    pub fn full_name(&self) -> &'a str {
+        &self.map.name
+    }
+
+    /// The part of the name before the first dot, if there is one
+    pub fn group(&self) -> Option<&'a str> {
+        self.full_name().split_once('.').map(|(group, _)| group)
+    }
+
+    /// The part of the name after the group
+    pub fn name(&self) -> &'a str {
+        self.full_name()
+            .split_once('.')
+            .map_or(self.full_name(), |(_, name)| name)
+    }
+
+    /// Whether the map stores absolute positions instead of displacements
+    pub fn is_absolute(&self) -> bool {
+        self.map.kind == VertexMapKind::AbsoluteMorph
+    }
+
+    /// The displacement of every mapped point relative to `base`
+    pub(crate) fn displacements<'b>(
+        &self,
+        base: &'b [[f32; 3]],
+    ) -> impl Iterator<Item = (usize, [f32; 3])> + 'b
+    where
+        'a: 'b,
+    {
+        let is_absolute = self.is_absolute();
+        // at most one of them matches the kind of the map
+        let positions = self.map.as_positions().into_iter().flatten();
+        let displacements = self.map.as_morph().into_iter().flatten();
+        positions
+            .chain(displacements)
+            .filter_map(move |(vert, value)| {
+                let point = *base.get(vert as usize)?;
+                Some((
+                    vert as usize,
+                    if is_absolute {
+                        sub(value, point)
+                    } else {
+                        value
+                    },
+                ))
+            })
+    }
+}
+
+/// The morph targets that share a group, see [MorphTarget::group].
+#[derive(Debug, Clone)]
+pub struct MorphGroup<'a> {
+    pub name: Option<&'a str>,
+    pub targets: Vec<MorphTarget<'a>>,
+}
+
+/// A morph target converted to the data layout glTF expects for a morph target's `POSITION`
+/// attribute: a displacement for every point, with the bounds that glTF requires for the
+/// accessor.
+///
+/// This is only the converted data. Writing it into a buffer and creating the buffer view,
+/// accessor and target entries is left to the glTF writer.
+///
+/// glTF uses right-handed coordinates, so Z is negated compared to LightWave. The base positions
+/// have to be converted the same way.
+///
+/// The displacements are indexed by LightWave point, while a glTF mesh needs one per vertex of
+/// the exported primitive. A mesh that splits points at VMAD seams, or that is exported per
+/// surface, has to remap the layout with [GltfMorphLayout::for_vertices].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMorphLayout {
+    /// The name, as listed in the mesh's `extras.targetNames`
+    pub name: String,
+    pub displacements: Vec<[f32; 3]>,
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl GltfMorphLayout {
+    /// The layout for the vertices of an exported primitive, given the point each vertex was
+    /// created from. Vertices split from the same point share its displacement, and points that
+    /// don't exist aren't displaced.
+    pub fn for_vertices(&self, points: &[u32]) -> GltfMorphLayout {
+        let displacements: Vec<[f32; 3]> = points
+            .iter()
+            .map(|&point| {
+                self.displacements
+                    .get(point as usize)
+                    .copied()
+                    .unwrap_or_default()
+            })
+            .collect();
+        let (min, max) = bounds(&displacements);
+        GltfMorphLayout {
+            name: self.name.clone(),
+            displacements,
+            min,
+            max,
+        }
+    }
+}
+
+impl<'a> LayerMesh<'a> {
+    /// All MORF and SPOT maps of the layer
+    pub fn morph_targets(&self) -> Vec<MorphTarget<'a>> {
+        self.vertex_maps
+            .iter()
+            .filter(|map| {
+                matches!(
+                    map.map.kind,
+                    VertexMapKind::Morph | VertexMapKind::AbsoluteMorph
+                )
+            })
+            .map(|map| MorphTarget { map: map.map })
+            .collect()
+    }
+
+    /// The morph targets grouped by the `Group.Name` convention, in the order their groups first
+    /// appear. Targets without a group are collected in a group without a name.
+    pub fn morph_groups(&self) -> Vec<MorphGroup<'a>> {
+        let mut groups: Vec<MorphGroup> = vec![];
+        for target in self.morph_targets() {
+            match groups.iter_mut().find(|group| group.name == target.group()) {
+                Some(group) => group.targets.push(target),
+                None => groups.push(MorphGroup {
+                    name: target.group(),
+                    targets: vec![target],
+                }),
+            }
+        }
+        groups
+    }
+
+    /// The first morph target with the given full name
+    pub fn morph_target(&self, full_name: &str) -> Option<MorphTarget<'a>> {
+        self.morph_targets()
+            .into_iter()
+            .find(|target| target.full_name() == full_name)
+    }
+
+    /// The positions of all points with a single target fully applied
+    pub fn morph_positions(&self, target: &MorphTarget) -> Vec<[f32; 3]> {
+        self.blend_morphs(&[(*target, 1.0)])
+    }
+
+    /// The positions of all points with every target applied by its weight. Displacements add
+    /// up, so absolute targets act like relative targets to the base positions.
+    pub fn blend_morphs(&self, targets: &[(MorphTarget, f32)]) -> Vec<[f32; 3]> {
+        let base = &self.points.point_location;
+        let mut positions = base.clone();
+        for (target, weight) in targets {
+            for (index, displacement) in target.displacements(base) {
+                positions[index] = add(positions[index], scale(displacement, *weight));
+            }
+        }
+        positions
+    }
+
+    /// Converts all morph targets to the data layout of glTF morph targets, with one
+    /// displacement per point, see [GltfMorphLayout]
+    pub fn gltf_morph_layouts(&self) -> Vec<GltfMorphLayout> {
+        let base = &self.points.point_location;
+        self.morph_targets()
+            .iter()
+            .map(|target| {
+                let mut displacements = vec![[0.0; 3]; base.len()];
+                for (index, [x, y, z]) in target.displacements(base) {
+                    displacements[index] = [x, y, -z];
+                }
+                let (min, max) = bounds(&displacements);
+                GltfMorphLayout {
+                    name: target.full_name().to_string(),
+                    displacements,
+                    min,
+                    max,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The bounds glTF requires for an accessor, which are zero for an empty one
+fn bounds(displacements: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    if displacements.is_empty() {
+        return ([0.0; 3], [0.0; 3]);
+    }
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for displacement in displacements {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(displacement[axis]);
+            max[axis] = max[axis].max(displacement[axis]);
+        }
+    }
+    (min, max)
+}
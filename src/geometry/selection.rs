@@ -0,0 +1,270 @@
+use crate::geometry::layers::{FlatMesh, FlatPolygonGroup, FlatVertexMap};
+use crate::geometry::mesh::{LayerMesh, PolygonGroup};
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::discontinuous_vertex_mapping::{
+    DiscontinuousVertexMapping, DiscontinuousVertexMappings,
+};
+use crate::lwo2::tags::point_list::PointList;
+use crate::lwo2::tags::polygon_list::{PolygonList, PolygonLists};
+use crate::lwo2::tags::polygon_tag_mapping::{
+    PolygonTagKind, PolygonTagMapping, PolygonTagMappings,
+};
+use crate::lwo2::tags::vertex_mapping::{VertexMapKind, VertexMapping, VertexMappings};
+use std::collections::HashMap;
+
+/// The polygons of one part, with only the points they use.
+#[derive(Debug)]
+pub struct PartMesh<'a> {
+    /// The part name, or `None` for the polygons without a PART tag
+    pub name: Option<&'a LwoString>,
+    /// The points and polygons of the part, in a single polygon group with the same kind as the
+    /// polygon list they were taken from, along with the entries of all VMAPs, PTAGs and VMADs
+    /// that refer to them. Maps and tags without any entries for the part are left out, and the
+    /// tag strings are those of the layer.
+    pub mesh: FlatMesh,
+    /// The index of each point in the layer's point list
+    pub source_points: Vec<u32>,
+    /// The index of each polygon in the polygon list it was taken from
+    pub source_polygons: Vec<u32>,
+}
+
+impl<'a> LayerMesh<'a> {
+    /// The names of all point selection sets, which are stored as PICK maps
+    pub fn selection_sets(&self) -> impl Iterator<Item = &'a LwoString> + '_ {
+        self.vertex_maps_of(VertexMapKind::Pick)
+            .map(|map| &map.map.name)
+    }
+
+    /// The points in the selection set with the given name, or `None` if there is no such set
+    pub fn points_in_set(&self, name: &str) -> Option<Vec<u32>> {
+        let map = self.vertex_map(VertexMapKind::Pick, name)?;
+        Some(map.map.as_selection()?.collect())
+    }
+
+    /// The names of all selection sets that contain `point`
+    pub fn sets_containing(&self, point: u32) -> Vec<&'a LwoString> {
+        self.vertex_maps_of(VertexMapKind::Pick)
+            .filter(|map| {
+                map.map
+                    .as_selection()
+                    .is_some_and(|mut points| points.any(|vert| vert == point))
+            })
+            .map(|map| &map.map.name)
+            .collect()
+    }
+
+    /// The names of all parts of a polygon list, in the order they first appear
+    pub fn part_names(&self, group: &PolygonGroup<'a>) -> Vec<&'a LwoString> {
+        let mut names: Vec<&LwoString> = vec![];
+        for (_, name) in self.polygon_parts(group) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// The polygons in the part with the given name
+    pub fn polygons_in_part(&self, group: &PolygonGroup<'a>, name: &str) -> Vec<u32> {
+        self.polygon_parts(group)
+            .filter(|(_, part)| *part == name)
+            .map(|(poly, _)| poly)
+            .collect()
+    }
+
+    /// Splits a polygon list into one mesh per part, in the order the parts first appear.
+    /// Polygons without a part come last, in a mesh without a name. Polygons that refer to
+    /// points that don't exist are skipped.
+    pub fn split_by_part(&self, group: &PolygonGroup<'a>) -> Vec<PartMesh<'a>> {
+        let mut names = vec![];
+        let mut part_indices = HashMap::new();
+        let mut polygon_parts = vec![None; group.polygons.polygons.len()];
+        for (poly, name) in self.polygon_parts(group) {
+            let part = *part_indices.entry(name).or_insert_with(|| {
+                names.push(Some(name));
+                names.len() - 1
+            });
+            if let Some(polygon_part) = polygon_parts.get_mut(poly as usize) {
+                *polygon_part = Some(part);
+            }
+        }
+        let untagged = names.len();
+        names.push(None);
+
+        let mut parts: Vec<PartMesh> = names
+            .into_iter()
+            .map(|name| PartMesh {
+                name,
+                mesh: FlatMesh {
+                    points: PointList {
+                        point_location: vec![],
+                    },
+                    vertex_maps: vec![],
+                    polygon_groups: vec![FlatPolygonGroup {
+                        polygons: PolygonLists {
+                            kind: group.polygons.kind,
+                            polygons: vec![],
+                        },
+                        tags: vec![],
+                        discontinuous_maps: vec![],
+                    }],
+                    tag_strings: self.tag_strings.to_vec(),
+                },
+                source_points: vec![],
+                source_polygons: vec![],
+            })
+            .collect();
+
+        // the parts every point of the layer ended up in, with its index in each of them
+        let points = &self.points.point_location;
+        let mut point_indices: Vec<Vec<(usize, u32)>> = vec![vec![]; points.len()];
+        // the part and the index within it of every polygon of the list
+        let mut polygon_indices = vec![None; group.polygons.polygons.len()];
+        for (index, polygon) in group.polygons.polygons.iter().enumerate() {
+            if polygon
+                .vert
+                .iter()
+                .any(|&vert| vert as usize >= points.len())
+            {
+                continue;
+            }
+            let part_index = polygon_parts[index].unwrap_or(untagged);
+            let part = &mut parts[part_index];
+            let vert = polygon
+                .vert
+                .iter()
+                .map(|&vert| {
+                    let indices = &mut point_indices[vert as usize];
+                    match indices.iter().find(|(part, _)| *part == part_index) {
+                        Some(&(_, index)) => index,
+                        None => {
+                            let index = part.source_points.len() as u32;
+                            part.mesh.points.point_location.push(points[vert as usize]);
+                            part.source_points.push(vert);
+                            indices.push((part_index, index));
+                            index
+                        }
+                    }
+                })
+                .collect();
+            let polygons = &mut part.mesh.polygon_groups[0].polygons.polygons;
+            polygon_indices[index] = Some((part_index, polygons.len() as u32));
+            polygons.push(PolygonList {
+                flags: polygon.flags,
+                vert,
+            });
+            part.source_polygons.push(index as u32);
+        }
+        let point_index = |part: usize, vert: u32| {
+            point_indices
+                .get(vert as usize)?
+                .iter()
+                .find(|(point_part, _)| *point_part == part)
+                .map(|&(_, index)| index)
+        };
+
+        for source in &self.vertex_maps {
+            let mut maps: Vec<VertexMappings> = parts
+                .iter()
+                .map(|_| VertexMappings {
+                    kind: source.map.kind,
+                    dimension: source.map.dimension,
+                    name: source.map.name.clone(),
+                    mapping: vec![],
+                })
+                .collect();
+            for mapping in &source.map.mapping {
+                for &(part, vert) in point_indices
+                    .get(mapping.vert as usize)
+                    .into_iter()
+                    .flatten()
+                {
+                    maps[part].mapping.push(VertexMapping {
+                        vert,
+                        value: mapping.value.clone(),
+                    });
+                }
+            }
+            for (part, map) in parts.iter_mut().zip(maps) {
+                if !map.mapping.is_empty() {
+                    part.mesh.vertex_maps.push(FlatVertexMap {
+                        map,
+                        parameter: source.parameter.cloned(),
+                    });
+                }
+            }
+        }
+
+        for source in &group.tags {
+            let mut tags: Vec<PolygonTagMappings> = parts
+                .iter()
+                .map(|_| PolygonTagMappings {
+                    kind: source.kind,
+                    mappings: vec![],
+                })
+                .collect();
+            for mapping in &source.mappings {
+                if let Some(&Some((part, poly))) = polygon_indices.get(mapping.poly as usize) {
+                    tags[part].mappings.push(PolygonTagMapping {
+                        poly,
+                        tag: mapping.tag,
+                    });
+                }
+            }
+            for (part, tags) in parts.iter_mut().zip(tags) {
+                if !tags.mappings.is_empty() {
+                    part.mesh.polygon_groups[0].tags.push(tags);
+                }
+            }
+        }
+
+        for source in &group.discontinuous_maps {
+            let mut maps: Vec<DiscontinuousVertexMappings> = parts
+                .iter()
+                .map(|_| DiscontinuousVertexMappings {
+                    kind: source.map.kind,
+                    dimension: source.map.dimension,
+                    name: source.map.name.clone(),
+                    mappings: vec![],
+                })
+                .collect();
+            for mapping in &source.map.mappings {
+                let Some(&Some((part, poly))) = polygon_indices.get(mapping.poly as usize) else {
+                    continue;
+                };
+                if let Some(vert) = point_index(part, mapping.vert) {
+                    maps[part].mappings.push(DiscontinuousVertexMapping {
+                        vert,
+                        poly,
+                        values: mapping.values.clone(),
+                    });
+                }
+            }
+            for (part, map) in parts.iter_mut().zip(maps) {
+                if !map.mappings.is_empty() {
+                    part.mesh.polygon_groups[0]
+                        .discontinuous_maps
+                        .push(FlatVertexMap {
+                            map,
+                            parameter: source.parameter.cloned(),
+                        });
+                }
+            }
+        }
+
+        parts.retain(|part| !part.source_polygons.is_empty());
+        parts
+    }
+
+    /// The part name of every tagged polygon of a polygon list
+    fn polygon_parts(
+        &self,
+        group: &PolygonGroup<'a>,
+    ) -> impl Iterator<Item = (u32, &'a LwoString)> + 'a {
+        group
+            .tags_of(PolygonTagKind::Part)
+            .and_then(|tags| tags.parts(self.tag_strings))
+            .into_iter()
+            .flatten()
+    }
+}
@@ -0,0 +1,157 @@
+use crate::geometry::math::{cross, distance, normalize, sub};
+use crate::geometry::mesh::{LayerMesh, PolygonGroup, VertexMapRef};
+use crate::geometry::tree::{self, ParentIndex};
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::polygon_tag_mapping::PolygonTagKind;
+use crate::lwo2::tags::vertex_mapping::{VertexMapKind, VertexMappings};
+use std::collections::HashMap;
+
+/// The bones of a BONE polygon list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skeleton {
+    pub bones: Vec<Bone>,
+}
+
+/// A single bone, made from a two-point BONE polygon that points from its first to its second
+/// point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bone {
+    /// The index of the BONE polygon
+    pub polygon: u32,
+    /// The name from the BONE polygon tag
+    pub name: Option<LwoString>,
+    /// The point the bone starts at
+    pub start: u32,
+    /// The point the bone ends at, which is shared with the start of its children
+    pub end: u32,
+    pub head: [f32; 3],
+    pub tail: [f32; 3],
+    /// The bone whose end is this bone's start
+    pub parent: Option<usize>,
+    /// The up vector from the BNUP polygon tag, if there is one
+    pub up: Option<[f32; 3]>,
+    /// The name of the WGHT map from the BNWT polygon tag
+    pub weight_map: Option<LwoString>,
+}
+
+impl Bone {
+    pub fn length(&self) -> f32 {
+        distance(self.head, self.tail)
+    }
+
+    /// The rest orientation as the bone's X, Y and Z axes. Z points along the bone and Y
+    /// towards the up vector, which defaults to +Y (or +Z for vertical bones).
+    pub fn rest_orientation(&self) -> [[f32; 3]; 3] {
+        let z = normalize(sub(self.tail, self.head)).unwrap_or([0.0, 0.0, 1.0]);
+        let x = [self.up, Some([0.0, 1.0, 0.0]), Some([0.0, 0.0, 1.0])]
+            .into_iter()
+            .flatten()
+            .find_map(|up| normalize(cross(up, z)))
+            .unwrap_or([1.0, 0.0, 0.0]);
+        [x, cross(z, x), z]
+    }
+}
+
+impl Skeleton {
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        tree::roots(&self.bones)
+    }
+
+    pub fn children(&self, bone: usize) -> impl Iterator<Item = usize> + '_ {
+        tree::children(&self.bones, bone)
+    }
+
+    /// The first bone with the given name
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.bones
+            .iter()
+            .position(|bone| bone.name.as_ref().is_some_and(|bone| bone == name))
+    }
+
+    /// All bones ordered so that parents come before their children
+    pub fn hierarchy_order(&self) -> Vec<usize> {
+        tree::hierarchy_order(&self.bones)
+    }
+}
+
+impl ParentIndex for Bone {
+    fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+impl<'a> LayerMesh<'a> {
+    /// Builds the skeleton of a BONE polygon list. Polygons that don't have exactly two valid
+    /// points are skipped. The hierarchy is inferred from shared points: a bone's parent is the
+    /// first bone that ends where it starts.
+    pub fn skeleton(&self, group: &PolygonGroup) -> Skeleton {
+        let points = &self.points.point_location;
+        let tag_strings = |kind: PolygonTagKind| -> HashMap<u32, &LwoString> {
+            group
+                .tags_of(kind)
+                .and_then(|tags| tags.names(self.tag_strings))
+                .map(|names| names.collect())
+                .unwrap_or_default()
+        };
+        let names = tag_strings(PolygonTagKind::Bone);
+        let ups = tag_strings(PolygonTagKind::BoneUp);
+        let weight_maps = tag_strings(PolygonTagKind::BoneWeightMap);
+
+        let mut bones: Vec<Bone> = group
+            .polygons
+            .polygons
+            .iter()
+            .enumerate()
+            .filter_map(|(index, polygon)| {
+                let index = index as u32;
+                let [start, end] = polygon.vert[..] else {
+                    return None;
+                };
+                Some(Bone {
+                    polygon: index,
+                    name: names.get(&index).map(|&name| name.clone()),
+                    start,
+                    end,
+                    head: *points.get(start as usize)?,
+                    tail: *points.get(end as usize)?,
+                    parent: None,
+                    up: ups.get(&index).and_then(|up| parse_vector(up)),
+                    weight_map: weight_maps.get(&index).map(|&name| name.clone()),
+                })
+            })
+            .collect();
+
+        // shared points could form a loop, which can't be a hierarchy
+        let parents = tree::acyclic_parents(
+            (0..bones.len())
+                .map(|bone| {
+                    (0..bones.len())
+                        .find(|&parent| parent != bone && bones[parent].end == bones[bone].start)
+                })
+                .collect(),
+        );
+        for (bone, parent) in bones.iter_mut().zip(parents) {
+            bone.parent = parent;
+        }
+        Skeleton { bones }
+    }
+
+    /// The WGHT map a bone is linked to with its BNWT tag
+    pub fn bone_weight_map(&self, bone: &Bone) -> Option<VertexMapRef<'a, VertexMappings>> {
+        self.vertex_map(VertexMapKind::Weight, bone.weight_map.as_ref()?)
+    }
+}
+
+/// Parses a vector stored as a tag string, with its components separated by spaces or commas
+fn parse_vector(string: &str) -> Option<[f32; 3]> {
+    let mut components = string
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|component| !component.is_empty())
+        .map(|component| component.parse::<f32>().ok());
+    let vector = [
+        components.next()??,
+        components.next()??,
+        components.next()??,
+    ];
+    components.next().is_none().then_some(vector)
+}
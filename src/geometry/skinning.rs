@@ -0,0 +1,253 @@
+use crate::geometry::math::{
+    add, distance, dot, linear, multiply, multiply3, scale, sub, transform_point, translation,
+    transpose3, IDENTITY,
+};
+use crate::geometry::mesh::LayerMesh;
+use crate::geometry::skeleton::{Bone, Skeleton};
+
+/// One transform per bone of a [Skeleton] that maps rest positions to posed positions, as
+/// row-major matrices applied to column vectors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pose {
+    pub transforms: Vec<[[f32; 4]; 4]>,
+}
+
+impl Pose {
+    /// The pose in which nothing moves
+    pub fn rest(skeleton: &Skeleton) -> Self {
+        Self {
+            transforms: vec![IDENTITY; skeleton.bones.len()],
+        }
+    }
+
+    /// Rotates every bone around its head, with the rotation given as rows of a 3x3 matrix in
+    /// the bone's [rest orientation](Bone::rest_orientation). Children follow the transforms of
+    /// their parents. Missing rotations are treated as identity.
+    pub fn from_rotations(skeleton: &Skeleton, rotations: &[[[f32; 3]; 3]]) -> Self {
+        let mut transforms = vec![IDENTITY; skeleton.bones.len()];
+        for bone_index in skeleton.hierarchy_order() {
+            let bone = &skeleton.bones[bone_index];
+            let Some(&rotation) = rotations.get(bone_index) else {
+                if let Some(parent) = bone.parent {
+                    transforms[bone_index] = transforms[parent];
+                }
+                continue;
+            };
+            // the rotation in object space is the local rotation in the bone's frame
+            let frame = transpose3(bone.rest_orientation());
+            let rotation = multiply3(multiply3(frame, rotation), transpose3(frame));
+            let local = multiply(
+                &translation(bone.head),
+                &multiply(&linear(rotation), &translation(scale(bone.head, -1.0))),
+            );
+            transforms[bone_index] = match bone.parent {
+                Some(parent) => multiply(&transforms[parent], &local),
+                None => local,
+            };
+        }
+        Self { transforms }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SkinningOptions {
+    /// The exponent of the inverse distance falloff for points without any weight, matching
+    /// LightWave's bone falloff type setting
+    pub falloff_exponent: i32,
+}
+
+impl Default for SkinningOptions {
+    fn default() -> Self {
+        Self {
+            falloff_exponent: 4,
+        }
+    }
+}
+
+impl LayerMesh<'_> {
+    /// The normalized bone influences of every point, as `(bone, weight)` pairs.
+    ///
+    /// Points with a positive weight in the WGHT map of at least one bone are influenced by
+    /// those bones only. All other points fall back to LightWave's default behavior of being
+    /// influenced by every bone with a weight of `1 / distance^falloff_exponent`, where the
+    /// distance is measured to the bone's segment.
+    pub fn skin_weights(
+        &self,
+        skeleton: &Skeleton,
+        options: &SkinningOptions,
+    ) -> Vec<Vec<(usize, f32)>> {
+        let points = &self.points.point_location;
+        let mut weights = vec![vec![]; points.len()];
+        for (bone_index, bone) in skeleton.bones.iter().enumerate() {
+            let Some(map) = self.bone_weight_map(bone) else {
+                continue;
+            };
+            for (vert, weight) in map.map.as_weights().into_iter().flatten() {
+                if let Some(weights) = weights.get_mut(vert as usize) {
+                    if weight > 0.0 {
+                        weights.push((bone_index, weight));
+                    }
+                }
+            }
+        }
+
+        for (point, weights) in points.iter().zip(&mut weights) {
+            if weights.is_empty() {
+                let distances: Vec<f32> = skeleton
+                    .bones
+                    .iter()
+                    .map(|bone| distance_to_bone(bone, *point))
+                    .collect();
+                // points on a bone only follow that bone
+                let on_bone = distances.iter().any(|&distance| distance <= f32::EPSILON);
+                *weights = distances
+                    .into_iter()
+                    .enumerate()
+                    .filter(|&(_, distance)| !on_bone || distance <= f32::EPSILON)
+                    .map(|(bone, distance)| {
+                        let weight = if on_bone {
+                            1.0
+                        } else {
+                            distance.powi(-options.falloff_exponent)
+                        };
+                        (bone, weight)
+                    })
+                    .collect();
+            }
+            let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+            if total.is_finite() && total > 0.0 {
+                for (_, weight) in weights.iter_mut() {
+                    *weight /= total;
+                }
+            } else {
+                // the falloff overflowed for points very close to a bone
+                let closest = weights.iter().copied().max_by(|a, b| a.1.total_cmp(&b.1));
+                *weights = closest
+                    .map(|(bone, _)| vec![(bone, 1.0)])
+                    .unwrap_or_default();
+            }
+        }
+        weights
+    }
+
+    /// Deforms all points of the layer with linear blend skinning, see
+    /// [LayerMesh::skin_weights] for how the bone influences are determined.
+    pub fn skin(
+        &self,
+        skeleton: &Skeleton,
+        pose: &Pose,
+        options: &SkinningOptions,
+    ) -> Vec<[f32; 3]> {
+        self.points
+            .point_location
+            .iter()
+            .zip(self.skin_weights(skeleton, options))
+            .map(|(&point, weights)| {
+                if weights.is_empty() {
+                    return point;
+                }
+                weights.iter().fold([0.0; 3], |sum, &(bone, weight)| {
+                    let transform = pose.transforms.get(bone).unwrap_or(&IDENTITY);
+                    add(sum, scale(transform_point(transform, point), weight))
+                })
+            })
+            .collect()
+    }
+}
+
+fn distance_to_bone(bone: &Bone, point: [f32; 3]) -> f32 {
+    let axis = sub(bone.tail, bone.head);
+    let length = dot(axis, axis);
+    let t = if length > 0.0 {
+        (dot(sub(point, bone.head), axis) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    distance(point, add(bone.head, scale(axis, t)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::fixtures::{assert_close, layer_mesh};
+    use crate::geometry::mesh::VertexMapRef;
+    use crate::lwo2::string::LwoString;
+    use crate::lwo2::tags::point_list::PointList;
+    use crate::lwo2::tags::polygon_list::{PolygonKind, PolygonList, PolygonLists};
+    use crate::lwo2::tags::vertex_mapping::{VertexMapKind, VertexMapping, VertexMappings};
+
+    fn weight_map(name: &str, weights: &[(u32, f32)]) -> VertexMappings {
+        VertexMappings {
+            kind: VertexMapKind::Weight,
+            dimension: 1,
+            name: LwoString::new(name),
+            mapping: weights
+                .iter()
+                .map(|&(vert, weight)| VertexMapping {
+                    vert,
+                    value: vec![weight],
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn linear_blend_of_two_bones() {
+        // a point weighted equally to two parallel bones along +Z, the first of which is
+        // rotated by 90° around its axis
+        let points = PointList {
+            point_location: vec![
+                [1.0, 0.0, 0.5],
+                [0.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0],
+                [5.0, 0.0, 0.0],
+                [5.0, 0.0, 1.0],
+            ],
+        };
+        let groups = [PolygonLists {
+            kind: PolygonKind::Bone,
+            polygons: vec![
+                PolygonList {
+                    flags: 0,
+                    vert: vec![1, 2],
+                },
+                PolygonList {
+                    flags: 0,
+                    vert: vec![3, 4],
+                },
+            ],
+        }];
+        let maps = [weight_map("A", &[(0, 0.5)]), weight_map("B", &[(0, 0.5)])];
+        let mut mesh = layer_mesh(&points, &groups);
+        mesh.vertex_maps = maps
+            .iter()
+            .map(|map| VertexMapRef {
+                map,
+                parameter: None,
+            })
+            .collect();
+
+        let mut skeleton = mesh.skeleton(&mesh.polygon_groups[0]);
+        assert_eq!(skeleton.bones.len(), 2);
+        assert_eq!(skeleton.bones[0].parent, None);
+        skeleton.bones[0].weight_map = Some(LwoString::new("A"));
+        skeleton.bones[1].weight_map = Some(LwoString::new("B"));
+
+        let options = SkinningOptions::default();
+        assert_eq!(
+            mesh.skin_weights(&skeleton, &options)[0],
+            vec![(0, 0.5), (1, 0.5)]
+        );
+
+        let rotation = [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+        let pose = Pose::from_rotations(&skeleton, &[rotation]);
+        let skinned = mesh.skin(&skeleton, &pose, &options);
+        // halfway between (0, 1, 0.5) from the rotated bone and (1, 0, 0.5) from the other
+        assert_close(skinned[0], [0.5, 0.5, 0.5]);
+
+        let rest = mesh.skin(&skeleton, &Pose::rest(&skeleton), &options);
+        for (skinned, point) in rest.iter().zip(&points.point_location) {
+            assert_close(*skinned, *point);
+        }
+    }
+}
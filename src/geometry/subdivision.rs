@@ -0,0 +1,552 @@
+use crate::geometry::mesh::{LayerMesh, PolygonGroup};
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::point_list::PointList;
+use crate::lwo2::tags::polygon_list::{PolygonKind, PolygonList, PolygonLists};
+use crate::lwo2::tags::vertex_map_parameter::UvSubdivisionType;
+use crate::lwo2::tags::vertex_mapping::VertexMapKind;
+use std::collections::HashMap;
+
+/// The result of [LayerMesh::subdivide].
+#[derive(Debug)]
+pub struct SubdividedMesh {
+    pub points: PointList,
+    /// A FACE list of quads, except for level 0, which returns the cage itself
+    pub polygons: PolygonLists,
+    /// The index of the cage polygon each polygon was created from
+    pub source_polygons: Vec<u32>,
+    pub uv_maps: Vec<SubdividedUvMap>,
+}
+
+/// The UV coordinates of every vertex of every polygon of a [SubdividedMesh].
+#[derive(Debug)]
+pub struct SubdividedUvMap {
+    pub name: LwoString,
+    pub uvs: Vec<Vec<[f32; 2]>>,
+}
+
+impl LayerMesh<'_> {
+    /// Subdivides the polygons of `group`, usually a PTCH list, `level` times using
+    /// Catmull-Clark subdivision. Every level splits each polygon with n vertices into n quads,
+    /// so after the first level the mesh consists of quads only and each further level
+    /// quadruples their number.
+    ///
+    /// Boundary edges are kept as creases with the usual boundary rules. The first MNVW map of
+    /// the layer pulls the surface towards the cage where the weight is positive and pushes it
+    /// away where it is negative. All TXUV maps, including the VMADs of the group, are
+    /// subdivided according to the `uv_subdivision_type` of their VMPA, or linearly if there is
+    /// none:
+    ///
+    /// * **Subpatch** smooths UVs like positions, with UV seams as boundaries.
+    /// * **SubpatchLinearCorners** additionally keeps UV corners, vertices with a single face, in
+    ///   place.
+    /// * **SubpatchLinearEdges** keeps all UV boundaries, seams and mesh boundaries, linear.
+    /// * **SubpatchDiscoEdges** only keeps seams linear, while mesh boundaries are smoothed.
+    ///
+    /// Polygons with fewer than three vertices, repeated vertices or missing points are skipped.
+    pub fn subdivide(&self, group: &PolygonGroup, level: u32) -> SubdividedMesh {
+        let points = &self.points.point_location;
+        let mut point_indices = vec![None; points.len()];
+        let mut cage = Cage::<3>::default();
+        let mut source_polygons = vec![];
+        for (index, polygon) in group.polygons.polygons.iter().enumerate() {
+            if !is_valid_face(&polygon.vert, points.len()) {
+                continue;
+            }
+            let face = polygon
+                .vert
+                .iter()
+                .map(|&vert| {
+                    *point_indices[vert as usize].get_or_insert_with(|| {
+                        cage.values.push(points[vert as usize]);
+                        cage.values.len() as u32 - 1
+                    })
+                })
+                .collect();
+            cage.faces.push(face);
+            source_polygons.push(index as u32);
+        }
+
+        let mut weights = self
+            .vertex_maps_of(VertexMapKind::SubpatchWeight)
+            .find_map(|map| map.map.as_weights())
+            .map(|map| {
+                let mut weights = vec![0.0; cage.values.len()];
+                for (vert, weight) in map {
+                    if let Some(Some(index)) = point_indices.get(vert as usize) {
+                        weights[*index as usize] = weight.clamp(-1.0, 1.0);
+                    }
+                }
+                weights
+            });
+
+        let mut uv_maps: Vec<(LwoString, UvSubdivisionType, Cage<2>)> = uv_map_names(self, group)
+            .into_iter()
+            .map(|name| {
+                let (kind, uv_cage) = uv_cage(self, group, &name, &point_indices, &cage);
+                (name, kind, uv_cage)
+            })
+            .collect();
+
+        for _ in 0..level {
+            let topology = Topology::new(cage.values.len(), &cage.faces);
+            let rules = Rules {
+                linear_edges: (0..topology.edges.len())
+                    .map(|edge| topology.is_boundary(edge))
+                    .collect(),
+                corners: vec![false; cage.values.len()],
+            };
+            for (_, kind, uv_cage) in &mut uv_maps {
+                let uv_topology = Topology::new(uv_cage.values.len(), &uv_cage.faces);
+                let uv_rules = Rules::for_uvs(*kind, &uv_topology, &topology);
+                *uv_cage = uv_cage.subdivide(&uv_topology, &uv_rules, None);
+            }
+            let subdivided = cage.subdivide(&topology, &rules, weights.as_deref());
+            weights = weights.map(|weights| topology.interpolate(&cage.faces, &weights));
+            source_polygons = cage
+                .faces
+                .iter()
+                .zip(&source_polygons)
+                .flat_map(|(face, &source)| face.iter().map(move |_| source))
+                .collect();
+            cage = subdivided;
+        }
+
+        SubdividedMesh {
+            points: PointList {
+                point_location: cage.values,
+            },
+            polygons: PolygonLists {
+                kind: PolygonKind::Face,
+                polygons: cage
+                    .faces
+                    .into_iter()
+                    .map(|vert| PolygonList { flags: 0, vert })
+                    .collect(),
+            },
+            source_polygons,
+            uv_maps: uv_maps
+                .into_iter()
+                .map(|(name, _, uv_cage)| SubdividedUvMap {
+                    name,
+                    uvs: uv_cage
+                        .faces
+                        .iter()
+                        .map(|face| {
+                            face.iter()
+                                .map(|&index| uv_cage.values[index as usize])
+                                .collect()
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn is_valid_face(vert: &[u32], point_count: usize) -> bool {
+    vert.len() >= 3
+        && vert.iter().all(|&index| (index as usize) < point_count)
+        && vert
+            .iter()
+            .enumerate()
+            .all(|(i, index)| !vert[i + 1..].contains(index))
+}
+
+/// The names of all TXUV VMAPs of the mesh and VMADs of the group
+fn uv_map_names(mesh: &LayerMesh, group: &PolygonGroup) -> Vec<LwoString> {
+    let mut names: Vec<LwoString> = vec![];
+    let maps = mesh
+        .vertex_maps_of(VertexMapKind::Uv)
+        .map(|map| &map.map.name);
+    let discontinuous_maps = group
+        .discontinuous_maps
+        .iter()
+        .filter(|map| map.map.kind == VertexMapKind::Uv)
+        .map(|map| &map.map.name);
+    for name in maps.chain(discontinuous_maps) {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+    names
+}
+
+/// Looks up the UVs of every face vertex, preferring the VMAD over the VMAP. Face vertices that
+/// share both point and UV share a UV vertex, all others are separated by seams.
+fn uv_cage(
+    mesh: &LayerMesh,
+    group: &PolygonGroup,
+    name: &str,
+    point_indices: &[Option<u32>],
+    cage: &Cage<3>,
+) -> (UvSubdivisionType, Cage<2>) {
+    let map = mesh.vertex_map(VertexMapKind::Uv, name);
+    let discontinuous_map = group.discontinuous_map(VertexMapKind::Uv, name);
+    let kind = map
+        .and_then(|map| map.parameter)
+        .or_else(|| discontinuous_map.and_then(|map| map.parameter))
+        .map_or(UvSubdivisionType::Linear, |parameter| {
+            parameter.uv_subdivision_type
+        });
+
+    let mut uvs = HashMap::new();
+    for (vert, uv) in map.and_then(|map| map.map.as_uv()).into_iter().flatten() {
+        if let Some(Some(index)) = point_indices.get(vert as usize) {
+            uvs.insert(*index, uv);
+        }
+    }
+    let mut discontinuous_uvs = HashMap::new();
+    let maps = discontinuous_map.and_then(|map| map.map.as_uv());
+    for (vert, poly, uv) in maps.into_iter().flatten() {
+        if let Some(Some(index)) = point_indices.get(vert as usize) {
+            discontinuous_uvs.insert((*index, poly), uv);
+        }
+    }
+
+    let mut uv_cage = Cage::<2>::default();
+    let mut uv_indices = HashMap::new();
+    let polygons = group
+        .polygons
+        .polygons
+        .iter()
+        .enumerate()
+        .filter(|(_, polygon)| is_valid_face(&polygon.vert, mesh.points.point_location.len()));
+    for (face, (poly, _)) in cage.faces.iter().zip(polygons) {
+        let uv_face = face
+            .iter()
+            .map(|&index| {
+                let uv = discontinuous_uvs
+                    .get(&(index, poly as u32))
+                    .or_else(|| uvs.get(&index))
+                    .copied()
+                    .unwrap_or_default();
+                *uv_indices
+                    .entry((index, uv[0].to_bits(), uv[1].to_bits()))
+                    .or_insert_with(|| {
+                        uv_cage.values.push(uv);
+                        uv_cage.values.len() as u32 - 1
+                    })
+            })
+            .collect();
+        uv_cage.faces.push(uv_face);
+    }
+    (kind, uv_cage)
+}
+
+/// Polygons over a list of values, which are either positions or UVs.
+#[derive(Debug, Default)]
+struct Cage<const N: usize> {
+    values: Vec<[f32; N]>,
+    faces: Vec<Vec<u32>>,
+}
+
+impl<const N: usize> Cage<N> {
+    /// Applies one level of subdivision. The new values are the vertex points followed by the
+    /// edge points and the face points, and every face is split into one quad per corner.
+    fn subdivide(&self, topology: &Topology, rules: &Rules, weights: Option<&[f32]>) -> Self {
+        let value = |index: u32| self.values[index as usize];
+        let face_points: Vec<[f32; N]> = self
+            .faces
+            .iter()
+            .map(|face| average(face.iter().map(|&index| value(index))))
+            .collect();
+        let midpoint = |edge: usize| {
+            let [a, b] = topology.edges[edge];
+            average([value(a), value(b)])
+        };
+
+        let edge_points = topology.edges.iter().enumerate().map(|(edge, &[a, b])| {
+            let linear = midpoint(edge);
+            if rules.linear_edges[edge] {
+                return linear;
+            }
+            let faces = &topology.edge_faces[edge];
+            let smooth = average([
+                value(a),
+                value(b),
+                face_points[faces[0] as usize],
+                face_points[faces[1] as usize],
+            ]);
+            match weights {
+                Some(weights) => lerp(
+                    smooth,
+                    linear,
+                    (weights[a as usize] + weights[b as usize]) / 2.0,
+                ),
+                None => smooth,
+            }
+        });
+
+        let vertex_points = self.values.iter().enumerate().map(|(vertex, &linear)| {
+            let edges = &topology.vertex_edges[vertex];
+            let faces = &topology.vertex_faces[vertex];
+            if rules.corners[vertex] || faces.is_empty() {
+                return linear;
+            }
+            let creases: Vec<usize> = edges
+                .iter()
+                .map(|&edge| edge as usize)
+                .filter(|&edge| rules.linear_edges[edge])
+                .collect();
+            let smooth = match creases[..] {
+                [first, second] => {
+                    let other = |edge: usize| {
+                        let [a, b] = topology.edges[edge];
+                        value(if a as usize == vertex { b } else { a })
+                    };
+                    weighted_sum([
+                        (other(first), 0.125),
+                        (linear, 0.75),
+                        (other(second), 0.125),
+                    ])
+                }
+                [] | [_] => {
+                    let n = edges.len() as f32;
+                    let face_average =
+                        average(faces.iter().map(|&face| face_points[face as usize]));
+                    let edge_average = average(edges.iter().map(|&edge| midpoint(edge as usize)));
+                    weighted_sum([
+                        (face_average, 1.0 / n),
+                        (edge_average, 2.0 / n),
+                        (linear, (n - 3.0) / n),
+                    ])
+                }
+                _ => return linear,
+            };
+            match weights {
+                Some(weights) => lerp(smooth, linear, weights[vertex]),
+                None => smooth,
+            }
+        });
+
+        let values = vertex_points
+            .chain(edge_points)
+            .chain(face_points.iter().copied())
+            .collect();
+        let edge_offset = self.values.len() as u32;
+        let face_offset = edge_offset + topology.edges.len() as u32;
+        let faces = self
+            .faces
+            .iter()
+            .zip(&topology.face_edges)
+            .enumerate()
+            .flat_map(|(face, (vertices, edges))| {
+                (0..vertices.len()).map(move |corner| {
+                    let previous = (corner + vertices.len() - 1) % vertices.len();
+                    vec![
+                        vertices[corner],
+                        edge_offset + edges[corner],
+                        face_offset + face as u32,
+                        edge_offset + edges[previous],
+                    ]
+                })
+            })
+            .collect();
+        Self { values, faces }
+    }
+}
+
+/// The edges of a cage and the faces and edges around every vertex.
+struct Topology {
+    edges: Vec<[u32; 2]>,
+    edge_faces: Vec<Vec<u32>>,
+    /// The edge from every corner of a face to the next one
+    face_edges: Vec<Vec<u32>>,
+    vertex_edges: Vec<Vec<u32>>,
+    vertex_faces: Vec<Vec<u32>>,
+}
+
+impl Topology {
+    fn new(vertex_count: usize, faces: &[Vec<u32>]) -> Self {
+        let mut topology = Topology {
+            edges: vec![],
+            edge_faces: vec![],
+            face_edges: Vec::with_capacity(faces.len()),
+            vertex_edges: vec![vec![]; vertex_count],
+            vertex_faces: vec![vec![]; vertex_count],
+        };
+        let mut edge_indices = HashMap::new();
+        for (face_index, face) in faces.iter().enumerate() {
+            let mut face_edges = Vec::with_capacity(face.len());
+            for (corner, &a) in face.iter().enumerate() {
+                let b = face[(corner + 1) % face.len()];
+                let edge = *edge_indices.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let edge = topology.edges.len() as u32;
+                    topology.edges.push([a, b]);
+                    topology.edge_faces.push(vec![]);
+                    topology.vertex_edges[a as usize].push(edge);
+                    topology.vertex_edges[b as usize].push(edge);
+                    edge
+                });
+                topology.edge_faces[edge as usize].push(face_index as u32);
+                topology.vertex_faces[a as usize].push(face_index as u32);
+                face_edges.push(edge);
+            }
+            topology.face_edges.push(face_edges);
+        }
+        topology
+    }
+
+    /// Edges that don't have exactly two faces can only be subdivided as creases
+    fn is_boundary(&self, edge: usize) -> bool {
+        self.edge_faces[edge].len() != 2
+    }
+
+    /// Linearly interpolates per-vertex values for the next level, in the same layout as
+    /// [Cage::subdivide]
+    fn interpolate(&self, faces: &[Vec<u32>], values: &[f32]) -> Vec<f32> {
+        let value = |index: &u32| values[*index as usize];
+        values
+            .iter()
+            .copied()
+            .chain(self.edges.iter().map(|[a, b]| (value(a) + value(b)) / 2.0))
+            .chain(
+                faces
+                    .iter()
+                    .map(|face| face.iter().map(value).sum::<f32>() / face.len() as f32),
+            )
+            .collect()
+    }
+}
+
+/// Which edges are subdivided linearly and which vertices are kept in place
+struct Rules {
+    linear_edges: Vec<bool>,
+    corners: Vec<bool>,
+}
+
+impl Rules {
+    fn for_uvs(kind: UvSubdivisionType, uv_topology: &Topology, topology: &Topology) -> Self {
+        let linear = kind == UvSubdivisionType::Linear;
+        let mut rules = Rules {
+            linear_edges: vec![linear; uv_topology.edges.len()],
+            corners: vec![linear; uv_topology.vertex_faces.len()],
+        };
+        for (edge, faces) in uv_topology.edge_faces.iter().enumerate() {
+            if !uv_topology.is_boundary(edge) {
+                continue;
+            }
+            rules.linear_edges[edge] = true;
+            // UV faces are parallel to the faces of the geometry, which tells whether the edge
+            // is a seam or a boundary of the mesh
+            let face = faces[0] as usize;
+            let corner = uv_topology.face_edges[face]
+                .iter()
+                .position(|&face_edge| face_edge as usize == edge)
+                .unwrap_or_default();
+            let is_seam = !topology.is_boundary(topology.face_edges[face][corner] as usize);
+            let keep = match kind {
+                UvSubdivisionType::SubpatchLinearEdges => true,
+                UvSubdivisionType::SubpatchDiscoEdges => is_seam,
+                _ => false,
+            };
+            if keep {
+                for vertex in uv_topology.edges[edge] {
+                    rules.corners[vertex as usize] = true;
+                }
+            }
+        }
+        if kind == UvSubdivisionType::SubpatchLinearCorners {
+            for (vertex, faces) in uv_topology.vertex_faces.iter().enumerate() {
+                if faces.len() == 1 {
+                    rules.corners[vertex] = true;
+                }
+            }
+        }
+        rules
+    }
+}
+
+fn average<const N: usize>(values: impl IntoIterator<Item = [f32; N]>) -> [f32; N] {
+    let mut sum = [0.0; N];
+    let mut count = 0;
+    for value in values {
+        for (sum, value) in sum.iter_mut().zip(value) {
+            *sum += value;
+        }
+        count += 1;
+    }
+    sum.map(|sum| sum / count.max(1) as f32)
+}
+
+fn weighted_sum<const N: usize, const M: usize>(terms: [([f32; N], f32); M]) -> [f32; N] {
+    let mut sum = [0.0; N];
+    for (value, weight) in terms {
+        for (sum, value) in sum.iter_mut().zip(value) {
+            *sum += value * weight;
+        }
+    }
+    sum
+}
+
+fn lerp<const N: usize>(a: [f32; N], b: [f32; N], t: f32) -> [f32; N] {
+    weighted_sum([(a, 1.0 - t), (b, t)])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::fixtures::{assert_close, cube_points, faces, layer_mesh, CUBE_FACES};
+
+    #[test]
+    fn cube_subdivided_once() {
+        let points = cube_points();
+        let groups = [faces(&CUBE_FACES)];
+        let mesh = layer_mesh(&points, &groups);
+        let subdivided = mesh.subdivide(&mesh.polygon_groups[0], 1);
+
+        assert_eq!(subdivided.polygons.polygons.len(), 24);
+        assert!(subdivided
+            .polygons
+            .polygons
+            .iter()
+            .all(|polygon| polygon.vert.len() == 4));
+        assert_eq!(subdivided.source_polygons.len(), 24);
+
+        // every corner moves to (F + 2R) / 3 with the face points F and edge midpoints R
+        // around it, every edge point is the average of its ends and both face points, and
+        // every face point is the center of its face
+        let mut expected = vec![];
+        for index in 0..8 {
+            expected.push([index & 1, index >> 1 & 1, index >> 2].map(|value| {
+                if value == 0 {
+                    2.0 / 9.0
+                } else {
+                    7.0 / 9.0
+                }
+            }));
+        }
+        for axis in 0..3 {
+            let [u, v] = [(axis + 1) % 3, (axis + 2) % 3];
+            for a in [0.125, 0.875] {
+                for b in [0.125, 0.875] {
+                    let mut edge_point = [0.5; 3];
+                    edge_point[u] = a;
+                    edge_point[v] = b;
+                    expected.push(edge_point);
+                }
+            }
+            for side in [0.0, 1.0] {
+                let mut face_point = [0.5; 3];
+                face_point[axis] = side;
+                expected.push(face_point);
+            }
+        }
+
+        let points = &subdivided.points.point_location;
+        assert_eq!(points.len(), expected.len());
+        for expected in expected {
+            let point = points
+                .iter()
+                .min_by(|a, b| {
+                    let distance = |point: &&[f32; 3]| {
+                        (0..3)
+                            .map(|axis| (point[axis] - expected[axis]).abs())
+                            .sum::<f32>()
+                    };
+                    distance(a).total_cmp(&distance(b))
+                })
+                .unwrap();
+            assert_close(*point, expected);
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use crate::geometry::mesh::{LayerMesh, PolygonGroup};
+use crate::lwo2::tags::point_list::PointList;
+use crate::lwo2::tags::polygon_list::{PolygonKind, PolygonLists};
+use std::collections::HashMap;
+
+/// The connectivity of FACE polygons as half-edges. Every polygon has one half-edge per side,
+/// going from each vertex to the next one in the polygon's vertex order. Consecutive repeats of
+/// the same point are treated as a single vertex, so that they don't form sides of zero length.
+///
+/// Edges shared by more than two polygons are kept, so that non-manifold geometry can be
+/// inspected rather than rejected.
+#[derive(Debug, Clone)]
+pub struct HalfEdgeMesh {
+    pub half_edges: Vec<HalfEdge>,
+    pub edges: Vec<Edge>,
+    /// The first half-edge of every polygon, or `None` for polygons that were skipped because
+    /// they have fewer than three distinct consecutive vertices or refer to points that don't
+    /// exist
+    pub polygons: Vec<Option<u32>>,
+    /// The half-edges starting at every point
+    pub point_half_edges: Vec<Vec<u32>>,
+    edge_indices: HashMap<(u32, u32), u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HalfEdge {
+    /// The point the half-edge starts at
+    pub origin: u32,
+    pub polygon: u32,
+    /// The next half-edge of the same polygon
+    pub next: u32,
+    /// The previous half-edge of the same polygon
+    pub prev: u32,
+    /// The half-edge going the opposite way along the same edge, if exactly two polygons share
+    /// the edge and they are oriented consistently
+    pub twin: Option<u32>,
+    pub edge: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    /// The points, lower index first
+    pub points: [u32; 2],
+    /// The half-edges along the edge, one for every polygon side that uses it
+    pub half_edges: Vec<u32>,
+}
+
+/// A set of polygons that are connected through shared points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub polygons: Vec<u32>,
+    pub points: Vec<u32>,
+}
+
+impl HalfEdgeMesh {
+    /// Builds the half-edges of a FACE polygon list, or returns `None` for other kinds
+    pub fn new(polygons: &PolygonLists, points: &PointList) -> Option<HalfEdgeMesh> {
+        if polygons.kind != PolygonKind::Face {
+            return None;
+        }
+        let point_count = points.point_location.len();
+        let mut mesh = HalfEdgeMesh {
+            half_edges: vec![],
+            edges: vec![],
+            polygons: Vec::with_capacity(polygons.polygons.len()),
+            point_half_edges: vec![vec![]; point_count],
+            edge_indices: HashMap::new(),
+        };
+
+        for (index, polygon) in polygons.polygons.iter().enumerate() {
+            let mut vert = polygon.vert.clone();
+            vert.dedup();
+            while vert.len() > 1 && vert.first() == vert.last() {
+                vert.pop();
+            }
+            if vert.len() < 3 || vert.iter().any(|&vert| vert as usize >= point_count) {
+                mesh.polygons.push(None);
+                continue;
+            }
+            let first = mesh.half_edges.len() as u32;
+            let count = vert.len() as u32;
+            for (corner, &a) in vert.iter().enumerate() {
+                let corner = corner as u32;
+                let b = vert[(corner as usize + 1) % vert.len()];
+                let half_edge = first + corner;
+                let edge = *mesh
+                    .edge_indices
+                    .entry((a.min(b), a.max(b)))
+                    .or_insert_with(|| {
+                        mesh.edges.push(Edge {
+                            points: [a.min(b), a.max(b)],
+                            half_edges: vec![],
+                        });
+                        mesh.edges.len() as u32 - 1
+                    });
+                mesh.edges[edge as usize].half_edges.push(half_edge);
+                mesh.point_half_edges[a as usize].push(half_edge);
+                mesh.half_edges.push(HalfEdge {
+                    origin: a,
+                    polygon: index as u32,
+                    next: first + (corner + 1) % count,
+                    prev: first + (corner + count - 1) % count,
+                    twin: None,
+                    edge,
+                });
+            }
+            mesh.polygons.push(Some(first));
+        }
+
+        for edge in &mesh.edges {
+            if let &[a, b] = edge.half_edges.as_slice() {
+                if mesh.half_edges[a as usize].origin != mesh.half_edges[b as usize].origin {
+                    mesh.half_edges[a as usize].twin = Some(b);
+                    mesh.half_edges[b as usize].twin = Some(a);
+                }
+            }
+        }
+        Some(mesh)
+    }
+
+    /// The point a half-edge ends at
+    pub fn destination(&self, half_edge: u32) -> u32 {
+        self.half_edges[self.half_edges[half_edge as usize].next as usize].origin
+    }
+
+    /// The half-edges of a polygon, in its vertex order
+    pub fn polygon_half_edges(&self, polygon: u32) -> impl Iterator<Item = u32> + '_ {
+        let first = self.polygons.get(polygon as usize).copied().flatten();
+        std::iter::successors(first, move |&half_edge| {
+            Some(self.half_edges[half_edge as usize].next).filter(|&next| Some(next) != first)
+        })
+    }
+
+    /// The edge between two points, in either direction
+    pub fn edge_between(&self, a: u32, b: u32) -> Option<u32> {
+        self.edge_indices.get(&(a.min(b), a.max(b))).copied()
+    }
+
+    /// The polygons that use an edge
+    pub fn edge_polygons(&self, edge: u32) -> impl Iterator<Item = u32> + '_ {
+        self.edges[edge as usize]
+            .half_edges
+            .iter()
+            .map(|&half_edge| self.half_edges[half_edge as usize].polygon)
+    }
+
+    /// The polygons that share at least one edge with a polygon, in the order of its edges
+    pub fn polygon_neighbors(&self, polygon: u32) -> Vec<u32> {
+        let mut neighbors = vec![];
+        for half_edge in self.polygon_half_edges(polygon) {
+            let edge = self.half_edges[half_edge as usize].edge;
+            for neighbor in self.edge_polygons(edge) {
+                if neighbor != polygon && !neighbors.contains(&neighbor) {
+                    neighbors.push(neighbor);
+                }
+            }
+        }
+        neighbors
+    }
+
+    /// Whether only one polygon uses an edge
+    pub fn is_boundary(&self, edge: u32) -> bool {
+        self.edges[edge as usize].half_edges.len() == 1
+    }
+
+    /// Whether every edge is shared by exactly two consistently oriented polygons, so that the
+    /// polygons enclose a volume
+    pub fn is_closed(&self) -> bool {
+        !self.half_edges.is_empty()
+            && self
+                .half_edges
+                .iter()
+                .all(|half_edge| half_edge.twin.is_some())
+    }
+
+    /// The points around every hole, following the vertex order of the polygons along it.
+    /// Where several holes touch at a point, the loops are split there, and where a boundary runs
+    /// into a non-manifold edge, the open chain is returned with both of its ends.
+    pub fn boundary_loops(&self) -> Vec<Vec<u32>> {
+        let is_boundary =
+            |half_edge: u32| self.is_boundary(self.half_edges[half_edge as usize].edge);
+        let mut used = vec![false; self.half_edges.len()];
+        let mut loops = vec![];
+        for start in 0..self.half_edges.len() as u32 {
+            if used[start as usize] || !is_boundary(start) {
+                continue;
+            }
+            let origin = self.half_edges[start as usize].origin;
+            let mut points = vec![];
+            let mut half_edge = start;
+            loop {
+                used[half_edge as usize] = true;
+                points.push(self.half_edges[half_edge as usize].origin);
+                let destination = self.destination(half_edge);
+                if destination == origin {
+                    break;
+                }
+                match self.point_half_edges[destination as usize]
+                    .iter()
+                    .find(|&&next| !used[next as usize] && is_boundary(next))
+                {
+                    Some(&next) => half_edge = next,
+                    None => {
+                        points.push(destination);
+                        break;
+                    }
+                }
+            }
+            loops.push(points);
+        }
+        loops
+    }
+
+    /// All edges shared by more than two polygons
+    pub fn non_manifold_edges(&self) -> Vec<u32> {
+        (0..self.edges.len() as u32)
+            .filter(|&edge| self.edges[edge as usize].half_edges.len() > 2)
+            .collect()
+    }
+
+    /// All points that are on a non-manifold edge, or whose polygons don't form a single fan
+    /// around them, such as the point where two cones touch at their tips
+    pub fn non_manifold_vertices(&self) -> Vec<u32> {
+        (0..self.point_half_edges.len() as u32)
+            .filter(|&point| !self.is_manifold_vertex(point))
+            .collect()
+    }
+
+    fn is_manifold_vertex(&self, point: u32) -> bool {
+        let corners = &self.point_half_edges[point as usize];
+        let mut edges: Vec<u32> = corners
+            .iter()
+            .flat_map(|&corner| {
+                let half_edge = &self.half_edges[corner as usize];
+                [
+                    half_edge.edge,
+                    self.half_edges[half_edge.prev as usize].edge,
+                ]
+            })
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        // corners are connected if their polygons share an edge at the point
+        let mut fans = UnionFind::new(corners.len());
+        for edge in edges {
+            let half_edges = &self.edges[edge as usize].half_edges;
+            if half_edges.len() > 2 {
+                return false;
+            }
+            let corner_of = |half_edge: u32| {
+                let corner = if self.half_edges[half_edge as usize].origin == point {
+                    half_edge
+                } else {
+                    self.half_edges[half_edge as usize].next
+                };
+                corners.iter().position(|&other| other == corner)
+            };
+            if let &[a, b] = half_edges.as_slice() {
+                if let (Some(a), Some(b)) = (corner_of(a), corner_of(b)) {
+                    fans.union(a, b);
+                }
+            }
+        }
+        (0..corners.len())
+            .filter(|&corner| fans.find(corner) == corner)
+            .count()
+            <= 1
+    }
+
+    /// The groups of polygons that share points, for layers that hold several disjoint meshes,
+    /// in the order of their first polygons
+    pub fn connected_components(&self) -> Vec<Component> {
+        let mut points = UnionFind::new(self.point_half_edges.len());
+        for (index, half_edge) in self.half_edges.iter().enumerate() {
+            points.union(
+                half_edge.origin as usize,
+                self.destination(index as u32) as usize,
+            );
+        }
+
+        let mut components: Vec<Component> = vec![];
+        let mut component_indices = HashMap::new();
+        for (polygon, first) in self.polygons.iter().enumerate() {
+            let Some(first) = first else { continue };
+            let root = points.find(self.half_edges[*first as usize].origin as usize);
+            let index = *component_indices.entry(root).or_insert_with(|| {
+                components.push(Component {
+                    polygons: vec![],
+                    points: vec![],
+                });
+                components.len() - 1
+            });
+            components[index].polygons.push(polygon as u32);
+        }
+        for point in 0..self.point_half_edges.len() {
+            if self.point_half_edges[point].is_empty() {
+                continue;
+            }
+            if let Some(&index) = component_indices.get(&points.find(point)) {
+                components[index].points.push(point as u32);
+            }
+        }
+        components
+    }
+
+    /// V - E + F, counting only the points that polygons use. It is 2 for a closed mesh that is
+    /// topologically a sphere, and decreases by two for every handle.
+    pub fn euler_characteristic(&self) -> i64 {
+        let vertices = self
+            .point_half_edges
+            .iter()
+            .filter(|half_edges| !half_edges.is_empty())
+            .count() as i64;
+        let faces = self.polygons.iter().flatten().count() as i64;
+        vertices - self.edges.len() as i64 + faces
+    }
+}
+
+impl LayerMesh<'_> {
+    /// The half-edges of a FACE polygon list, see [HalfEdgeMesh::new]
+    pub fn half_edges(&self, group: &PolygonGroup) -> Option<HalfEdgeMesh> {
+        HalfEdgeMesh::new(group.polygons, self.points)
+    }
+}
+
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(count: usize) -> Self {
+        UnionFind {
+            parents: (0..count).collect(),
+        }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[b] = a;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::fixtures::{cube_points, faces, CUBE_FACES};
+
+    #[test]
+    fn closed_cube() {
+        let mesh = HalfEdgeMesh::new(&faces(&CUBE_FACES), &cube_points()).unwrap();
+        assert!(mesh.is_closed());
+        assert_eq!(mesh.euler_characteristic(), 2);
+        assert!(mesh.boundary_loops().is_empty());
+        assert!(mesh.non_manifold_edges().is_empty());
+    }
+
+    #[test]
+    fn cube_with_one_face_removed() {
+        // without the top face, whose points are 4, 5, 7 and 6
+        let open: Vec<[u32; 4]> = CUBE_FACES
+            .into_iter()
+            .filter(|&face| face != [4, 5, 7, 6])
+            .collect();
+        let mesh = HalfEdgeMesh::new(&faces(&open), &cube_points()).unwrap();
+        assert!(!mesh.is_closed());
+        assert_eq!(mesh.euler_characteristic(), 1);
+
+        let loops = mesh.boundary_loops();
+        assert_eq!(loops.len(), 1);
+        let mut points = loops[0].clone();
+        points.sort();
+        assert_eq!(points, [4, 5, 6, 7]);
+        // the side polygons run along the hole in the opposite direction of the missing face
+        let start = loops[0].iter().position(|&point| point == 4).unwrap();
+        let mut rotated = loops[0].clone();
+        rotated.rotate_left(start);
+        assert_eq!(rotated, [4, 6, 7, 5]);
+    }
+}
@@ -0,0 +1,56 @@
+//! Hierarchies stored as an optional parent index on every node, such as layers and bones.
+
+/// A node of a hierarchy that refers to its parent by index.
+pub(crate) trait ParentIndex {
+    fn parent(&self) -> Option<usize>;
+}
+
+/// Resolves the parent candidate of every node, in order. Candidates that don't exist, that are
+/// the node itself or that would make the hierarchy circular are replaced by `None`.
+pub(crate) fn acyclic_parents(candidates: Vec<Option<usize>>) -> Vec<Option<usize>> {
+    let mut parents = vec![None; candidates.len()];
+    for (node, &candidate) in candidates.iter().enumerate() {
+        let parent = candidate.filter(|&parent| parent != node && parent < candidates.len());
+        let mut ancestor = parent;
+        let mut depth = 0;
+        while let Some(current) = ancestor {
+            if current == node || depth > candidates.len() {
+                break;
+            }
+            ancestor = parents[current];
+            depth += 1;
+        }
+        if ancestor.is_none() {
+            parents[node] = parent;
+        }
+    }
+    parents
+}
+
+pub(crate) fn roots<T: ParentIndex>(nodes: &[T]) -> impl Iterator<Item = usize> + '_ {
+    (0..nodes.len()).filter(|&node| nodes[node].parent().is_none())
+}
+
+pub(crate) fn children<T: ParentIndex>(
+    nodes: &[T],
+    node: usize,
+) -> impl Iterator<Item = usize> + '_ {
+    (0..nodes.len()).filter(move |&child| nodes[child].parent() == Some(node))
+}
+
+/// All nodes ordered so that parents come before their children
+pub(crate) fn hierarchy_order<T: ParentIndex>(nodes: &[T]) -> Vec<usize> {
+    let mut children = vec![vec![]; nodes.len()];
+    for (node, parent) in nodes.iter().enumerate() {
+        if let Some(parent) = parent.parent() {
+            children[parent].push(node);
+        }
+    }
+    let mut order: Vec<usize> = roots(nodes).collect();
+    let mut index = 0;
+    while index < order.len() {
+        order.extend_from_slice(&children[order[index]]);
+        index += 1;
+    }
+    order
+}
@@ -0,0 +1,69 @@
+use crate::read_options::chunk_data;
+use binrw::{binread, BinRead};
+use std::ops::{Deref, DerefMut};
+
+pub mod raw;
+
+#[binread]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chunk<D>
+where
+    for<'a> D: BinRead<Args<'a> = (u32,)>,
+{
+    pub length: u32,
+    #[br(pad_size_to = length, align_after = 2, args(length), parse_with = chunk_data(length))]
+    pub data: D,
+}
+
+impl<D> Deref for Chunk<D>
+where
+    for<'a> D: BinRead<Args<'a> = (u32,)>,
+{
+    type Target = D;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<D> DerefMut for Chunk<D>
+where
+    for<'a> D: BinRead<Args<'a> = (u32,)>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.data
+    }
+}
+
+#[binread]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubChunk<D>
+where
+    for<'a> D: BinRead<Args<'a> = (u32,)>,
+{
+    pub length: u16,
+    #[br(pad_size_to = length, align_after = 2, args(length as u32), parse_with = chunk_data(length as u32))]
+    pub data: D,
+}
+
+impl<D> Deref for SubChunk<D>
+where
+    for<'a> D: BinRead<Args<'a> = (u32,)>,
+{
+    type Target = D;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<D> DerefMut for SubChunk<D>
+where
+    for<'a> D: BinRead<Args<'a> = (u32,)>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.data
+    }
+}
@@ -0,0 +1,253 @@
+use crate::read_options::{read_scoped, with_read_options, ReadOptions};
+use binrw::{BinRead, BinResult, BinWrite, Endian};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+/// The size of the length field that follows a chunk ID. IFF-85 always uses 32 bit lengths,
+/// LightWave additionally uses 16 bit lengths for subchunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LengthWidth {
+    U16,
+    #[default]
+    U32,
+}
+
+impl LengthWidth {
+    /// The size of a chunk header, i.e. the ID and the length
+    pub fn header_size(self) -> u64 {
+        match self {
+            Self::U16 => 6,
+            Self::U32 => 8,
+        }
+    }
+}
+
+/// The IFF-85 group chunks, which contain a type ID followed by nested chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroupKind {
+    /// A single data object, such as an LWO2 object, made up of its properties as chunks
+    Form,
+    /// A collection of groups that share the properties given by the `PROP` groups at its start
+    List,
+    /// A concatenation of groups without any shared properties
+    Cat,
+    /// Shared properties for the groups of a `LIST`
+    Prop,
+}
+
+impl GroupKind {
+    pub fn from_id(id: &[u8; 4]) -> Option<Self> {
+        match id {
+            b"FORM" => Some(Self::Form),
+            b"LIST" => Some(Self::List),
+            b"CAT " => Some(Self::Cat),
+            b"PROP" => Some(Self::Prop),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> [u8; 4] {
+        match self {
+            Self::Form => *b"FORM",
+            Self::List => *b"LIST",
+            Self::Cat => *b"CAT ",
+            Self::Prop => *b"PROP",
+        }
+    }
+}
+
+/// A chunk of any IFF file, either a group with nested chunks or opaque data.
+///
+/// Reading and writing takes the [LengthWidth] of the chunk as an argument, which is also used
+/// for all nested chunks. IFF is big endian, so use [BinRead::read_be] and [BinWrite::write_be].
+///
+/// ```
+/// use binrw::{BinRead, BinWrite};
+/// use lightwave_3d::iff::raw::{GroupKind, RawChunk};
+/// use std::io::Cursor;
+///
+/// let form = RawChunk::Group {
+///     kind: GroupKind::Form,
+///     id: *b"LWO2",
+///     children: vec![RawChunk::Data {
+///         id: *b"TAGS",
+///         data: b"Default\0".to_vec(),
+///     }],
+/// };
+/// let mut buffer = Cursor::new(vec![]);
+/// form.write_be(&mut buffer).unwrap();
+/// buffer.set_position(0);
+/// assert_eq!(RawChunk::read_be(&mut buffer).unwrap(), form);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RawChunk {
+    Group {
+        kind: GroupKind,
+        /// The type of the group's contents, `LWO2` for LightWave objects for example
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::four_cc"))]
+        id: [u8; 4],
+        children: Vec<RawChunk>,
+    },
+    Data {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::four_cc"))]
+        id: [u8; 4],
+        data: Vec<u8>,
+    },
+}
+
+impl RawChunk {
+    /// Reads a single chunk while enforcing the limits given in `options`.
+    pub fn read_with_options<R>(
+        reader: &mut R,
+        width: LengthWidth,
+        options: &ReadOptions,
+    ) -> BinResult<RawChunk>
+    where
+        R: Read + Seek,
+    {
+        with_read_options(options, || RawChunk::read_be_args(reader, (width,)))
+    }
+
+    /// Parses a sequence of chunks from a byte slice, such as the subchunks contained in the
+    /// data of a chunk.
+    pub fn parse_all(data: &[u8], width: LengthWidth) -> BinResult<Vec<RawChunk>> {
+        read_chunks(
+            &mut Cursor::new(data),
+            Endian::Big,
+            width,
+            data.len() as u64,
+        )
+    }
+
+    /// The chunk ID, which is the group kind's ID for groups
+    pub fn chunk_id(&self) -> [u8; 4] {
+        match self {
+            Self::Group { kind, .. } => kind.id(),
+            Self::Data { id, .. } => *id,
+        }
+    }
+
+    /// The value of the length field, which excludes the header and the padding byte
+    pub fn length(&self, width: LengthWidth) -> u64 {
+        match self {
+            Self::Group { children, .. } => {
+                4 + children.iter().map(|child| child.size(width)).sum::<u64>()
+            }
+            Self::Data { data, .. } => data.len() as u64,
+        }
+    }
+
+    /// The total size of the chunk including header and padding
+    pub fn size(&self, width: LengthWidth) -> u64 {
+        let length = self.length(width);
+        width.header_size() + length + (length & 1)
+    }
+
+    pub fn children(&self) -> &[RawChunk] {
+        match self {
+            Self::Group { children, .. } => children,
+            Self::Data { .. } => &[],
+        }
+    }
+
+    /// Finds the first direct child with the given chunk ID
+    pub fn find(&self, id: &[u8; 4]) -> Option<&RawChunk> {
+        self.children().iter().find(|child| &child.chunk_id() == id)
+    }
+}
+
+impl BinRead for RawChunk {
+    type Args<'a> = (LengthWidth,);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        (width,): Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let id = <[u8; 4]>::read_options(reader, endian, ())?;
+        let length = match width {
+            LengthWidth::U16 => u16::read_options(reader, endian, ())? as u64,
+            LengthWidth::U32 => u32::read_options(reader, endian, ())? as u64,
+        };
+        let start = reader.stream_position()?;
+
+        let chunk = read_scoped(reader, length, |reader| match GroupKind::from_id(&id) {
+            Some(kind) if length >= 4 => Ok(RawChunk::Group {
+                kind,
+                id: <[u8; 4]>::read_options(reader, endian, ())?,
+                children: read_chunks(reader, endian, width, length - 4)?,
+            }),
+            _ => {
+                let mut data = Vec::new();
+                let read = reader.take(length).read_to_end(&mut data)?;
+                if (read as u64) < length {
+                    return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+                }
+                Ok(RawChunk::Data { id, data })
+            }
+        })?;
+
+        reader.seek(SeekFrom::Start(start + length + (length & 1)))?;
+        Ok(chunk)
+    }
+}
+
+/// Reads chunks until `length` bytes have been consumed
+pub fn read_chunks<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    width: LengthWidth,
+    length: u64,
+) -> BinResult<Vec<RawChunk>> {
+    let start = reader.stream_position()?;
+    let mut chunks = vec![];
+    while reader.stream_position()? - start + width.header_size() <= length {
+        chunks.push(RawChunk::read_options(reader, endian, (width,))?);
+    }
+    Ok(chunks)
+}
+
+impl BinWrite for RawChunk {
+    type Args<'a> = (LengthWidth,);
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        (width,): Self::Args<'_>,
+    ) -> BinResult<()> {
+        let pos = writer.stream_position()?;
+        self.chunk_id().write_options(writer, endian, ())?;
+        let length = self.length(width);
+        match width {
+            LengthWidth::U16 => u16::try_from(length)
+                .map_err(|_| binrw::Error::AssertFail {
+                    pos,
+                    message: format!("chunk length {} doesn't fit into 16 bits", length),
+                })?
+                .write_options(writer, endian, ())?,
+            LengthWidth::U32 => u32::try_from(length)
+                .map_err(|_| binrw::Error::AssertFail {
+                    pos,
+                    message: format!("chunk length {} doesn't fit into 32 bits", length),
+                })?
+                .write_options(writer, endian, ())?,
+        }
+
+        match self {
+            Self::Group { id, children, .. } => {
+                id.write_options(writer, endian, ())?;
+                for child in children {
+                    child.write_options(writer, endian, (width,))?;
+                }
+            }
+            Self::Data { data, .. } => writer.write_all(data)?,
+        }
+        if length & 1 == 1 {
+            writer.write_all(&[0])?;
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,26 @@
 use crate::lwo2::tags::Tag;
+use crate::read_options::{form_length, with_read_options, ReadOptions};
 use binrw::{binread, until_eof, BinRead, BinResult};
 use std::fs::File;
 use std::io::{Read, Seek};
 use std::path::Path;
 
+#[cfg(feature = "tokio")]
+mod async_read;
 mod binrw_helpers;
+mod chunk_length;
+pub mod cleanup;
+pub mod geometry;
 pub mod iff;
 pub mod lwo2;
+pub mod read_options;
+mod references;
+#[cfg(feature = "serde")]
+mod serde_helpers;
+pub mod split;
+pub mod transform;
+pub mod validate;
+pub mod visit;
 
 /// The data in LightWave 3D® object files comprise the points, polygons and surfaces that describe
 /// the geometry and appearance of an object. "Polygons" here means any of several geometric
@@ -37,8 +51,10 @@ pub mod lwo2;
 /// size to seek to the next chunk.
 #[binread]
 #[br(big, magic(b"FORM"))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LightWaveObject {
+    #[br(parse_with = form_length)]
     pub file_size: u32,
     #[br(magic(b"LWO2"), parse_with = until_eof)]
     pub data: Vec<Tag>,
@@ -46,8 +62,15 @@ pub struct LightWaveObject {
 
 impl LightWaveObject {
     pub fn read_file<P: AsRef<Path>>(path: P) -> std::io::Result<LightWaveObject> {
+        Self::read_file_with_options(path, &ReadOptions::default())
+    }
+
+    pub fn read_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &ReadOptions,
+    ) -> std::io::Result<LightWaveObject> {
         let mut reader = File::open(path)?;
-        Self::read(&mut reader)
+        Self::read_with_options(&mut reader, options)
             .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
     }
 
@@ -55,6 +78,15 @@ impl LightWaveObject {
     where
         R: Read + Seek,
     {
-        BinRead::read(reader)
+        Self::read_with_options(reader, &ReadOptions::default())
+    }
+
+    /// Reads an object while enforcing the limits given in `options`, which should be used
+    /// for any input that isn't trusted.
+    pub fn read_with_options<R>(reader: &mut R, options: &ReadOptions) -> BinResult<LightWaveObject>
+    where
+        R: Read + Seek,
+    {
+        with_read_options(options, || BinRead::read(reader))
     }
 }
@@ -1,6 +1,7 @@
 use binrw::{BinReaderExt, BinResult, Endian};
 use std::io::{Read, Seek};
 
+pub mod string;
 pub mod sub_tags;
 pub mod tags;
 
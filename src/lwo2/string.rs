@@ -0,0 +1,155 @@
+use crate::read_options::StringDecoder;
+use binrw::{BinResult, BinWrite, Endian};
+use std::fmt;
+use std::io::{Seek, Write};
+use std::ops::Deref;
+
+/// A null-terminated string as stored in the file.
+///
+/// The spec only allows ASCII, but files written by localized versions of LightWave® often
+/// contain Latin-1 or Shift-JIS names. The raw bytes are therefore kept alongside the decoded
+/// string, so that nothing is lost when decoding fails or the wrong decoder was chosen, and
+/// writing the string emits exactly the bytes that were read.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LwoString {
+    raw: Vec<u8>,
+    string: String,
+}
+
+impl LwoString {
+    /// Creates a string that is stored as UTF-8
+    pub fn new(string: impl Into<String>) -> Self {
+        let string = string.into();
+        Self {
+            raw: string.as_bytes().to_vec(),
+            string,
+        }
+    }
+
+    pub fn from_raw(raw: Vec<u8>, decoder: StringDecoder) -> Self {
+        Self {
+            string: decoder.decode(&raw),
+            raw,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.string
+    }
+
+    /// The bytes as stored in the file, without the terminating null byte and padding
+    pub fn raw_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// Decodes the raw bytes again using a different decoder
+    pub fn redecode(&mut self, decoder: StringDecoder) {
+        self.string = decoder.decode(&self.raw)
+    }
+
+    pub fn into_string(self) -> String {
+        self.string
+    }
+}
+
+impl Deref for LwoString {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.string
+    }
+}
+
+impl fmt::Display for LwoString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+impl From<&str> for LwoString {
+    fn from(string: &str) -> Self {
+        Self::new(string)
+    }
+}
+
+impl From<String> for LwoString {
+    fn from(string: String) -> Self {
+        Self::new(string)
+    }
+}
+
+impl PartialEq<str> for LwoString {
+    fn eq(&self, other: &str) -> bool {
+        self.string == other
+    }
+}
+
+impl PartialEq<&str> for LwoString {
+    fn eq(&self, other: &&str) -> bool {
+        self.string == *other
+    }
+}
+
+impl PartialEq<String> for LwoString {
+    fn eq(&self, other: &String) -> bool {
+        &self.string == other
+    }
+}
+
+/// Writes the raw bytes followed by a null byte and, if needed, a padding byte.
+impl BinWrite for LwoString {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        _endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        writer.write_all(&self.raw)?;
+        writer.write_all(if self.raw.len() & 1 == 0 {
+            &[0, 0]
+        } else {
+            &[0]
+        })?;
+        Ok(())
+    }
+}
+
+/// Strings that decode losslessly are serialized as plain strings, all others additionally
+/// carry their raw bytes.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::LwoString;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Plain(String),
+        Raw { string: String, raw: Vec<u8> },
+    }
+
+    impl Serialize for LwoString {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if self.string.as_bytes() == self.raw.as_slice() {
+                serializer.serialize_str(&self.string)
+            } else {
+                Repr::Raw {
+                    string: self.string.clone(),
+                    raw: self.raw.clone(),
+                }
+                .serialize(serializer)
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for LwoString {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Ok(match Repr::deserialize(deserializer)? {
+                Repr::Plain(string) => LwoString::new(string),
+                Repr::Raw { string, raw } => LwoString { raw, string },
+            })
+        }
+    }
+}
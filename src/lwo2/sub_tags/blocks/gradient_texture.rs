@@ -3,7 +3,8 @@ use crate::lwo2::sub_tags::Name;
 use binrw::binread;
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GradientTextureSubChunk {
     #[br(magic(b"PNAM"))]
     ParameterName(SubChunk<Name>),
@@ -24,7 +25,8 @@ pub enum GradientTextureSubChunk {
 /// The repeat mode. This is currently undefined.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyParameters {
     #[br(count = length / 2)]
     pub repeat_mode: Vec<u16>,
@@ -37,14 +39,16 @@ pub struct KeyParameters {
 /// key.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyValues {
     #[br(count = length / 18)]
     pub key_values: Vec<KeyValue>,
 }
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyValue {
     pub input: f32,
     pub output: [f32; 4],
@@ -54,7 +58,8 @@ pub struct KeyValue {
 /// in the user interface. They don't affect rendering.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientRange {
     pub name: f32,
 }
@@ -62,7 +67,8 @@ pub struct GradientRange {
 /// The repeat mode. This is currently undefined.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RepeatMode {
     pub repeat_mode: u16,
 }
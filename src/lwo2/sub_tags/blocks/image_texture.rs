@@ -1,12 +1,14 @@
 use crate::binrw_helpers::lwo_null_string;
 use crate::iff::SubChunk;
+use crate::lwo2::string::LwoString;
 use crate::lwo2::sub_tags::blocks::texture_mapping::TextureMapping;
 use crate::lwo2::sub_tags::{ValueEnvelope, VxReference};
 use crate::lwo2::vx;
 use binrw::binread;
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SurfaceBlockImageTextureSubChunk {
     #[br(magic(b"TMAP"))]
     TextureMapping(SubChunk<TextureMapping>),
@@ -38,7 +40,8 @@ pub enum SurfaceBlockImageTextureSubChunk {
 /// for the X, Y or Z axis.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MajorAxis {
     pub texture_axis: u16,
 }
@@ -47,7 +50,8 @@ pub struct MajorAxis {
 /// image map pixel. If the low-order flag bit is set, then pixel blending is enabled.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PixelBlending {
     pub flags: u16,
 }
@@ -57,7 +61,8 @@ pub struct PixelBlending {
 /// area of the image.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AntialiasingStrength {
     pub flags: u16,
     pub strength: f32,
@@ -67,17 +72,19 @@ pub struct AntialiasingStrength {
 /// the TXUV vertex map that contains those coordinates.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UvMap {
     #[br(parse_with = lwo_null_string)]
-    pub txuv_map_name: String,
+    pub txuv_map_name: LwoString,
 }
 
 /// For cylindrical and spherical projections, these parameters control how many times the image
 /// repeats over each full interval.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImageWrapAmount {
     pub cycles: f32,
     #[br(parse_with = vx)]
@@ -87,7 +94,8 @@ pub struct ImageWrapAmount {
 /// Specifies how the color of the texture is derived for areas outside the image.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImageWrapOptions {
     pub width_wrap: ImageWrapType,
     pub height_wrap: ImageWrapType,
@@ -95,7 +103,8 @@ pub struct ImageWrapOptions {
 
 #[binread]
 #[br(repr = u16)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ImageWrapType {
     /// Areas outside the image are assumed to be black. The ultimate effect of this depends on
     /// the opacity settings. For an additive texture layer on the color channel, the final color
@@ -111,7 +120,8 @@ pub enum ImageWrapType {
 
 #[binread]
 #[br(repr = u16, import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProjectionMode {
     Planar = 0,
     Cylindrical = 1,
@@ -1,5 +1,6 @@
 use crate::binrw_helpers::{lwo_null_string, until_size_limit};
 use crate::iff::SubChunk;
+use crate::lwo2::string::LwoString;
 use crate::lwo2::sub_tags::blocks::gradient_texture::GradientTextureSubChunk;
 use crate::lwo2::sub_tags::blocks::image_texture::SurfaceBlockImageTextureSubChunk;
 use crate::lwo2::sub_tags::blocks::procedural_texture::ProceduralTextureSubChunk;
@@ -14,24 +15,25 @@ pub mod texture_mapping;
 
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SurfaceBlocks {
     #[br(magic(b"IMAP"))]
     ImageMapTexture {
         header: SubChunk<SurfaceBlockHeader>,
-        #[br(parse_with = until_size_limit(length as u64 - (header.length as u64 + 2 + 4)))]
+        #[br(parse_with = until_size_limit((length as u64).saturating_sub(header.length as u64 + 2 + 4)))]
         attributes: Vec<SurfaceBlockImageTextureSubChunk>,
     },
     #[br(magic(b"PROC"))]
     ProceduralTexture {
         header: SubChunk<SurfaceBlockHeader>,
-        #[br(parse_with = until_size_limit(length as u64 - (header.length as u64 + 2 + 4)))]
+        #[br(parse_with = until_size_limit((length as u64).saturating_sub(header.length as u64 + 2 + 4)))]
         attributes: Vec<ProceduralTextureSubChunk>,
     },
     #[br(magic(b"GRAD"))]
     GradientTexture {
         header: SubChunk<SurfaceBlockHeader>,
-        #[br(parse_with = until_size_limit(length as u64 - (header.length as u64 + 2 + 4)))]
+        #[br(parse_with = until_size_limit((length as u64).saturating_sub(header.length as u64 + 2 + 4)))]
         attributes: Vec<GradientTextureSubChunk>,
     },
     #[br(magic(b"SHDR"))]
@@ -44,25 +46,28 @@ pub enum SurfaceBlocks {
 
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Algorithm {
     #[br(parse_with = lwo_null_string)]
-    pub algorithm_name: String,
-    #[br(count = length - (algorithm_name.len() as u32 + 1))]
+    pub algorithm_name: LwoString,
+    #[br(count = length.saturating_sub(algorithm_name.raw_bytes().len() as u32 + 1))]
     pub data: Vec<u8>,
 }
 
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SurfaceBlockHeader {
     #[br(pad_before = 2)]
-    #[br(parse_with = until_size_limit(length as u64 - 4))]
+    #[br(parse_with = until_size_limit(length.saturating_sub(4) as u64))]
     pub block_attributes: Vec<SurfaceBlockHeaderSubChunk>,
 }
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SurfaceBlockHeaderSubChunk {
     #[br(magic(b"CHAN"))]
     Channel(SubChunk<Channel>),
@@ -78,14 +83,16 @@ pub enum SurfaceBlockHeaderSubChunk {
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DisplacementAxis {
     pub displacement_axis: u16,
 }
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Opacity {
     pub kind: OpacityType,
     pub opacity: f32,
@@ -95,7 +102,8 @@ pub struct Opacity {
 
 #[binread]
 #[br(repr = u16)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OpacityType {
     Normal = 0,
     Subtractive = 1,
@@ -109,13 +117,15 @@ pub enum OpacityType {
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Channel {
     pub texture_channel: TextureChannel,
 }
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextureChannel {
     #[br(magic(b"COLR"))]
     Color,
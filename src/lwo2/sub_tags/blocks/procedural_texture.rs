@@ -3,7 +3,8 @@ use crate::lwo2::sub_tags::blocks::Algorithm;
 use binrw::binread;
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProceduralTextureSubChunk {
     #[br(magic(b"AXIS"))]
     Axis(SubChunk<Axis>),
@@ -17,7 +18,8 @@ pub enum ProceduralTextureSubChunk {
 /// here. This may be a scalar or a vector.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BasicValue {
     #[br(count = length / 4)]
     pub value: Vec<f32>,
@@ -26,7 +28,8 @@ pub struct BasicValue {
 /// If the procedural has an axis, it may be defined with this chunk using a value of 0, 1 or 2.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Axis {
     pub axis: u16,
 }
@@ -1,19 +1,22 @@
 use crate::binrw_helpers::{lwo_null_string, until_size_limit};
 use crate::iff::SubChunk;
+use crate::lwo2::string::LwoString;
 use crate::lwo2::sub_tags::VectorEnvelope;
 use crate::lwo2::vx;
 use binrw::binread;
 
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextureMapping {
     #[br(parse_with = until_size_limit(length as u64))]
     pub attributes: Vec<TextureMappingSubChunk>,
 }
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextureMappingSubChunk {
     #[br(magic(b"CNTR"))]
     Center(SubChunk<VectorEnvelope>),
@@ -31,7 +34,8 @@ pub enum TextureMappingSubChunk {
 
 #[binread]
 #[br(repr = u16, import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CoordinateSystem {
     ObjectCoordinates = 0,
     WorldCoordinates = 1,
@@ -39,15 +43,17 @@ pub enum CoordinateSystem {
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReferenceObject {
     #[br(parse_with = lwo_null_string)]
-    pub object_name: String,
+    pub object_name: LwoString,
 }
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Falloff {
     pub kind: FalloffType,
     pub vector: [f32; 3],
@@ -57,7 +63,8 @@ pub struct Falloff {
 
 #[binread]
 #[br(repr = u16)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FalloffType {
     Cubic = 0,
     Spherical = 1,
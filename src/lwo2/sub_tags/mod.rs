@@ -1,4 +1,5 @@
 use crate::binrw_helpers::lwo_null_string;
+use crate::lwo2::string::LwoString;
 use crate::lwo2::vx;
 use binrw::binread;
 
@@ -8,7 +9,8 @@ pub mod surface_parameters;
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VectorEnvelope {
     pub base_color: [f32; 3],
     #[br(parse_with = vx)]
@@ -17,15 +19,17 @@ pub struct VectorEnvelope {
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Name {
     #[br(parse_with = lwo_null_string)]
-    pub name: String,
+    pub name: LwoString,
 }
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ValueEnvelope {
     pub value: f32,
     #[br(parse_with = vx)]
@@ -34,7 +38,8 @@ pub struct ValueEnvelope {
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VxReference {
     #[br(parse_with = vx)]
     pub texture_image: u32,
@@ -42,7 +47,8 @@ pub struct VxReference {
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnableState {
     pub enable: u16,
 }
@@ -1,17 +1,19 @@
 use crate::binrw_helpers::lwo_null_string;
+use crate::lwo2::string::LwoString;
 use binrw::{binread, PosValue};
 
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PluginServerNameAndData {
     #[br(temp)]
     start_pos: PosValue<()>,
     #[br(parse_with = lwo_null_string)]
-    pub server_name: String,
+    pub server_name: LwoString,
     pub flags: u16,
     #[br(temp)]
     end_pos: PosValue<()>,
-    #[br(count = length as u64 - (end_pos.pos - start_pos.pos))]
+    #[br(count = (length as u64).saturating_sub(end_pos.pos - start_pos.pos))]
     pub parameters: Vec<u8>,
 }
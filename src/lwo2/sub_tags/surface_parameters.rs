@@ -1,12 +1,15 @@
 use crate::binrw_helpers::lwo_null_string;
 use crate::iff::SubChunk;
+use crate::lwo2::string::LwoString;
 use crate::lwo2::sub_tags::blocks::SurfaceBlocks;
 use crate::lwo2::sub_tags::{ValueEnvelope, VectorEnvelope, VxReference};
+use crate::lwo2::tags::vertex_mapping::VertexMapKind;
 use crate::lwo2::vx;
 use binrw::binread;
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SurfaceParameterSubChunk {
     #[br(magic(b"COLR"))]
     BaseColor(SubChunk<VectorEnvelope>),
@@ -69,20 +72,23 @@ pub enum SurfaceParameterSubChunk {
 /// The vertex color map subchunk identifies an RGB or RGBA VMAP that will be used to color the surface.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VertexColorMap {
     pub intensity: f32,
     #[br(parse_with = vx)]
     pub envelope: u32,
-    pub vmap_type: [u8; 4],
+    #[br(map = |id: [u8; 4]| VertexMapKind::from(id))]
+    pub vmap_type: VertexMapKind,
     #[br(parse_with = lwo_null_string)]
-    pub name: String,
+    pub name: LwoString,
 }
 
 /// The alpha mode defines the alpha channel output options for the surface.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AlphaMode {
     pub mode: AlphaModeMode,
     pub value: f32,
@@ -90,7 +96,8 @@ pub struct AlphaMode {
 
 #[binread]
 #[br(repr = u16)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlphaModeMode {
     /// The surface has no effect on the alpha channel when rendered.
     UnaffectedBySurface = 0,
@@ -109,7 +116,8 @@ pub enum AlphaModeMode {
 /// possibly without size information (subchunk length 2).
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RenderOutlines {
     pub flags: u16,
     #[br(if(length > 2))]
@@ -132,7 +140,8 @@ pub struct RenderOutlines {
 /// and its envelope (the subchunk length is 6).
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlowEffect {
     pub kind: GlowType,
     pub intensity: f32,
@@ -147,7 +156,8 @@ pub struct GlowEffect {
 
 #[binread]
 #[br(repr = u16)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GlowType {
     HastingsGlow = 0,
     ImageConvolution = 1,
@@ -155,7 +165,8 @@ pub enum GlowType {
 
 #[binread]
 #[br(repr = u16, import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReflectionOptions {
     BackdropOnly = 0,
     RaytracingAndBackdrop = 1,
@@ -165,14 +176,16 @@ pub enum ReflectionOptions {
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PolygonSidedness {
     pub sidedness: u16,
 }
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MaxSmoothingAngle {
     pub max_smoothing_angle: f32,
 }
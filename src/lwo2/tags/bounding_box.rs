@@ -4,7 +4,8 @@ use binrw::binread;
 /// the lower and upper corners of the bounding box.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoundingBox {
     pub min: [f32; 3],
     pub max: [f32; 3],
@@ -1,4 +1,6 @@
 use crate::binrw_helpers::{lwo_null_string, until_size_limit};
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::vertex_mapping::VertexMapKind;
 use crate::lwo2::vx;
 use binrw::{binread, PosValue};
 
@@ -25,29 +27,86 @@ use binrw::{binread, PosValue};
 /// unsupported types are preserved but never evaluated.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiscontinuousVertexMappings {
     #[br(temp)]
     start_pos: PosValue<()>,
-    pub kind: [u8; 4],
-    #[br(temp)]
-    dimension: u16,
+    #[br(map = |id: [u8; 4]| VertexMapKind::from(id))]
+    pub kind: VertexMapKind,
+    pub dimension: u16,
     #[br(parse_with = lwo_null_string)]
-    pub name: String,
+    pub name: LwoString,
     #[br(temp)]
     end_pos: PosValue<()>,
-    #[br(parse_with = |reader, endian, _: ()| until_size_limit(length as u64 - (end_pos.pos - start_pos.pos))(reader, endian, (dimension, )))]
+    #[br(parse_with = |reader, endian, _: ()| until_size_limit((length as u64).saturating_sub(end_pos.pos - start_pos.pos))(reader, endian, (dimension, )))]
     pub mappings: Vec<DiscontinuousVertexMapping>,
 }
 
+impl DiscontinuousVertexMappings {
+    /// The weights of a WGHT or MNVW map as `(vert, poly, weight)`
+    pub fn as_weights(&self) -> Option<impl Iterator<Item = (u32, u32, f32)> + '_> {
+        let vectors = match self.kind {
+            VertexMapKind::Weight | VertexMapKind::SubpatchWeight => self.vectors::<1>(),
+            _ => None,
+        }?;
+        Some(vectors.map(|(vert, poly, [weight])| (vert, poly, weight)))
+    }
+
+    /// The coordinates of a TXUV map as `(vert, poly, uv)`
+    pub fn as_uv(&self) -> Option<impl Iterator<Item = (u32, u32, [f32; 2])> + '_> {
+        self.vectors_of(VertexMapKind::Uv)
+    }
+
+    /// The colors of an RGB map as `(vert, poly, rgb)`
+    pub fn as_rgb(&self) -> Option<impl Iterator<Item = (u32, u32, [f32; 3])> + '_> {
+        self.vectors_of(VertexMapKind::Rgb)
+    }
+
+    /// The colors of an RGBA map as `(vert, poly, rgba)`
+    pub fn as_rgba(&self) -> Option<impl Iterator<Item = (u32, u32, [f32; 4])> + '_> {
+        self.vectors_of(VertexMapKind::Rgba)
+    }
+
+    /// The vectors of a NORM map as `(vert, poly, normal)`
+    pub fn as_normals(&self) -> Option<impl Iterator<Item = (u32, u32, [f32; 3])> + '_> {
+        self.vectors_of(VertexMapKind::Normal)
+    }
+
+    fn vectors_of<const N: usize>(
+        &self,
+        kind: VertexMapKind,
+    ) -> Option<impl Iterator<Item = (u32, u32, [f32; N])> + '_> {
+        if self.kind == kind {
+            self.vectors()
+        } else {
+            None
+        }
+    }
+
+    /// All vectors, if the map has a dimension of `N`
+    fn vectors<const N: usize>(&self) -> Option<impl Iterator<Item = (u32, u32, [f32; N])> + '_> {
+        (usize::from(self.dimension) == N).then(|| {
+            self.mappings.iter().filter_map(|mapping| {
+                Some((
+                    mapping.vert,
+                    mapping.poly,
+                    mapping.values.as_slice().try_into().ok()?,
+                ))
+            })
+        })
+    }
+}
+
 #[binread]
 #[br(import(dimension: u16))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiscontinuousVertexMapping {
     #[br(parse_with = vx)]
     pub vert: u32,
     #[br(parse_with = vx)]
     pub poly: u32,
-    #[br(count = dimension)]
+    #[br(count = u32::from(dimension))]
     pub values: Vec<f32>,
 }
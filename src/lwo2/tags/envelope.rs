@@ -1,12 +1,14 @@
 use crate::binrw_helpers::{lwo_null_string, until_size_limit};
 use crate::iff::SubChunk;
+use crate::lwo2::string::LwoString;
 use crate::lwo2::sub_tags::plugin::PluginServerNameAndData;
 use crate::lwo2::vx;
 use binrw::{binread, PosValue};
 
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnvelopeDefinition {
     #[br(temp)]
     pos_start: PosValue<()>,
@@ -14,20 +16,21 @@ pub struct EnvelopeDefinition {
     pub index: u32,
     #[br(temp)]
     pos_end: PosValue<()>,
-    #[br(parse_with = until_size_limit(length as u64 - (pos_end.pos - pos_start.pos)))]
+    #[br(parse_with = until_size_limit((length as u64).saturating_sub(pos_end.pos - pos_start.pos)))]
     pub attributes: Vec<EnvelopeSubChunk>,
 }
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EnvelopeSubChunk {
     #[br(magic(b"TYPE"))]
     EnvelopeType(SubChunk<EnvelopeType>),
-    #[br(magic(b"PRE"))]
+    #[br(magic(b"PRE\0"))]
     PreBehavior(SubChunk<Behavior>),
     #[br(magic(b"POST"))]
     PostBehavior(SubChunk<Behavior>),
-    #[br(magic(b"KEY"))]
+    #[br(magic(b"KEY\0"))]
     KeyframeTimeAndValue(SubChunk<KeyframeTimeAndValue>),
     #[br(magic(b"SPAN"))]
     IntervalInterpolation(SubChunk<IntervalInterpolation>),
@@ -41,10 +44,11 @@ pub enum EnvelopeSubChunk {
 /// but plug-ins can browse the envelope database by name.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PluginChannelName {
     #[br(parse_with = lwo_null_string)]
-    pub channel_name: String,
+    pub channel_name: LwoString,
 }
 
 /// Defines the interpolation between the most recent KEY chunk and the KEY immediately before it in
@@ -53,15 +57,17 @@ pub struct PluginChannelName {
 /// Different parameters are stored for each of these.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IntervalInterpolation {
     pub kind: IntervalInterpolationType,
-    #[br(count = (length - 4) / 4)]
+    #[br(count = length.saturating_sub(4) / 4)]
     pub parameters: Vec<f32>,
 }
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IntervalInterpolationType {
     #[br(magic(b"STEP"))]
     Step,
@@ -81,7 +87,8 @@ pub enum IntervalInterpolationType {
 /// is interpolated. The time of a keyframe isn't restricted to integer frames.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyframeTimeAndValue {
     pub time: f32,
     pub value: f32,
@@ -89,7 +96,8 @@ pub struct KeyframeTimeAndValue {
 
 #[binread]
 #[br(repr = u16, import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Behavior {
     /// Sets the value to 0.0.
     Reset = 0,
@@ -110,7 +118,8 @@ pub enum Behavior {
 /// no effect on the actual values, only the way they're presented in LightWave®'s interface.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnvelopeType {
     pub user_format: UserFormat,
     pub kind: EnvelopeKind,
@@ -118,7 +127,8 @@ pub struct EnvelopeType {
 
 #[binread]
 #[br(repr = u8)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UserFormat {
     Float = 2,
     Distance = 3,
@@ -128,7 +138,8 @@ pub enum UserFormat {
 
 #[binread]
 #[br(repr = u8)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EnvelopeKind {
     PositionX = 0x1,
     PositionY = 0x2,
@@ -1,5 +1,6 @@
 use crate::binrw_helpers::{lwo_null_string, until_size_limit};
 use crate::iff::SubChunk;
+use crate::lwo2::string::LwoString;
 use crate::lwo2::sub_tags::plugin::PluginServerNameAndData;
 use crate::lwo2::sub_tags::{EnableState, ValueEnvelope};
 use binrw::{binread, PosValue};
@@ -11,15 +12,17 @@ use binrw::{binread, PosValue};
 /// list of subchunks, which are documented below in the Clip Subchunks section.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImageClip {
     pub index: u32,
-    #[br(parse_with = until_size_limit(length as u64 - 4))]
+    #[br(parse_with = until_size_limit(length.saturating_sub(4) as u64))]
     pub attributes: Vec<ImageClipSubChunk>,
 }
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ImageClipSubChunk {
     #[br(magic(b"STIL"))]
     StillImage(SubChunk<StillImage>),
@@ -63,14 +66,15 @@ pub enum ImageClipSubChunk {
 
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PluginAnimation {
     #[br(temp)]
     start_pos: PosValue<()>,
     #[br(parse_with = lwo_null_string)]
-    pub file_name: String,
+    pub file_name: LwoString,
     #[br(parse_with = lwo_null_string)]
-    pub server_name: String,
+    pub server_name: LwoString,
     pub flags: u16,
     #[br(temp)]
     end_pos: PosValue<()>,
@@ -83,12 +87,13 @@ pub struct PluginAnimation {
 /// is set to 1, then the file name of the color space is save as a local string.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColorSpace {
     pub flags: u16,
     pub color_space: u16,
     #[br(parse_with = lwo_null_string)]
-    pub file_name: String,
+    pub file_name: LwoString,
 }
 
 /// A still image with color-cycling is a source defined by a neutral-format name and cycling
@@ -100,18 +105,20 @@ pub struct ColorSpace {
 /// and are applied as filters layered on top of the source image.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColorCyclingStill {
     pub lo: i16,
     pub hi: i16,
     #[br(parse_with = lwo_null_string)]
-    pub name: String,
+    pub name: LwoString,
 }
 
 /// Defines source times for an animated clip.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Time {
     pub start_time: f32,
     pub duration: f32,
@@ -121,7 +128,8 @@ pub struct Time {
 /// TODO: What's this?
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Flags {
     pub flag: u32,
 }
@@ -129,10 +137,11 @@ pub struct Flags {
 /// The source is a single still image referenced by a filename in neutral path format.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StillImage {
     #[br(parse_with = lwo_null_string)]
-    pub name: String,
+    pub name: LwoString,
 }
 
 /// The source is a numbered sequence of still image files. Each filename contains a fixed number
@@ -146,7 +155,8 @@ pub struct StillImage {
 /// define the range of frames in the sequence.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImageSequence {
     pub num_digits: u8,
     pub flags: u8,
@@ -155,18 +165,19 @@ pub struct ImageSequence {
     pub start: i16,
     pub end: i16,
     #[br(parse_with = lwo_null_string)]
-    pub prefix: String,
+    pub prefix: LwoString,
     #[br(parse_with = lwo_null_string)]
-    pub suffix: String,
+    pub suffix: LwoString,
 }
 
 /// The source is a copy, or instance, of another clip, given by the index. The string is a unique
 /// name for this instance of the clip.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Reference {
     pub index: u32,
     #[br(parse_with = lwo_null_string)]
-    pub string: String,
+    pub string: LwoString,
 }
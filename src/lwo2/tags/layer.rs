@@ -1,4 +1,5 @@
 use crate::binrw_helpers::lwo_null_string;
+use crate::lwo2::string::LwoString;
 use binrw::{binread, PosValue};
 
 /// Signals the start of a new layer. All the data chunks which follow will be included in this
@@ -8,14 +9,29 @@ use binrw::{binread, PosValue};
 /// to indicate no parent.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Layer {
+    #[br(temp)]
+    begin_pos: PosValue<()>,
     pub number: u16,
     pub flags: u16,
     pub pivot: [f32; 3],
     #[br(parse_with = lwo_null_string)]
-    pub name: String,
-    pub status: PosValue<()>,
-    #[br(if(status.pos < length as u64))]
+    pub name: LwoString,
+    #[br(temp)]
+    end_pos: PosValue<()>,
+    #[br(if(end_pos.pos - begin_pos.pos < length as u64))]
     pub parent: Option<u16>,
 }
+
+impl Layer {
+    pub fn is_hidden(&self) -> bool {
+        self.flags & 1 == 1
+    }
+
+    /// The number of the parent layer, treating -1 as no parent
+    pub fn parent_number(&self) -> Option<u16> {
+        self.parent.filter(|&parent| parent != u16::MAX)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::binrw_helpers::lwo_null_string;
+use crate::lwo2::string::LwoString;
 use binrw::binread;
 
 /// Store an object description. Optional. This should be a simple line of upper and lowercase
@@ -6,10 +7,11 @@ use binrw::binread;
 /// should be no control characters in this text string and it should generally be kept short.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DescriptionLine {
     #[br(parse_with = lwo_null_string)]
-    pub description_line: String,
+    pub description_line: LwoString,
 }
 
 /// An iconic or thumbnail image for the object which can be used when viewing the file in a
@@ -18,19 +20,21 @@ pub struct DescriptionLine {
 /// is (chunkSize - 4)/width. This chunk is optional.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ThumbnailIconImage {
     pub encoding: ThumbnailImageEncoding,
     pub width: u16,
-    #[br(calc = (length as u16 - 4) / width)]
+    #[br(calc = (length.saturating_sub(4) as u16).checked_div(width).unwrap_or(0))]
     pub height: u16,
-    #[br(count = length - 4)]
+    #[br(count = length.saturating_sub(4))]
     pub data: Vec<u8>,
 }
 
 #[binread]
 #[br(repr = u16)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ThumbnailImageEncoding {
     UncompressedRgb = 0,
 }
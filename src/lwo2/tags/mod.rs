@@ -1,6 +1,7 @@
 use crate::iff::Chunk;
 use crate::lwo2::tags::bounding_box::BoundingBox;
 use crate::lwo2::tags::discontinuous_vertex_mapping::DiscontinuousVertexMappings;
+use crate::lwo2::tags::envelope::EnvelopeDefinition;
 use crate::lwo2::tags::image_clip::ImageClip;
 use crate::lwo2::tags::layer::Layer;
 use crate::lwo2::tags::meta::{DescriptionLine, ThumbnailIconImage};
@@ -28,7 +29,8 @@ pub mod vertex_map_parameter;
 pub mod vertex_mapping;
 
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Tag {
     #[br(magic(b"LAYR"))]
     Layer(Chunk<Layer>),
@@ -58,4 +60,6 @@ pub enum Tag {
     SurfaceDefinition(Chunk<SurfaceDefinition>),
     #[br(magic(b"CLIP"))]
     ImageClip(Chunk<ImageClip>),
+    #[br(magic(b"ENVL"))]
+    EnvelopeDefinition(Chunk<EnvelopeDefinition>),
 }
@@ -1,3 +1,5 @@
+use crate::binrw_helpers::count_limited;
+use crate::read_options::CountLimit;
 use binrw::binread;
 
 ///Lists (x, y, z) coordinate triples for a set of points. The number of points in the chunk is
@@ -9,8 +11,9 @@ use binrw::binread;
 /// unit is meters. Coordinates in PNTS are relative to the pivot point of the layer.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointList {
-    #[br(count = length / 12, assert(length % 12 == 0))]
+    #[br(parse_with = count_limited(CountLimit::Points, length as usize / 12), assert(length % 12 == 0))]
     pub point_location: Vec<[f32; 3]>,
 }
@@ -1,4 +1,5 @@
-use crate::binrw_helpers::{count_with_vx, until_size_limit};
+use crate::binrw_helpers::{count_with_vx, until_size_limit_limited};
+use crate::read_options::CountLimit;
 use binrw::binread;
 
 /// A list of polygons for the current layer. Possible polygon types include:
@@ -33,22 +34,129 @@ use binrw::binread;
 /// clockwise as seen from the visible side of the polygon. LightWave® polygons are single-sided
 /// (although double-sidedness is a possible surface property), and the normal is defined as the
 /// cross product of the first and last edges.
+///
+/// The flag bits are kept as they are stored, see [PolygonList::curve_flags] for their meaning.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PolygonLists {
-    pub kind: [u8; 4],
-    #[br(parse_with = until_size_limit(length as u64 - 4))]
+    #[br(map = |id: [u8; 4]| PolygonKind::from(id))]
+    pub kind: PolygonKind,
+    #[br(parse_with = |reader, endian, _: ()| until_size_limit_limited(length.saturating_sub(4) as u64, CountLimit::Polygons)(reader, endian, (kind,)))]
     pub polygons: Vec<PolygonList>,
 }
 
+/// The type of the polygons in a POLS chunk. Unknown types are preserved as
+/// [PolygonKind::Other].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PolygonKind {
+    /// `FACE`, regular polygons
+    Face,
+    /// `CURV`, Catmull-Rom splines
+    Curve,
+    /// `PTCH`, subdivision patch control cages
+    Patch,
+    /// `MBAL`, single-point metaballs
+    Metaball,
+    /// `BONE`, two-point skeleton bones
+    Bone,
+    Other([u8; 4]),
+}
+
+impl PolygonKind {
+    pub fn id(self) -> [u8; 4] {
+        match self {
+            Self::Face => *b"FACE",
+            Self::Curve => *b"CURV",
+            Self::Patch => *b"PTCH",
+            Self::Metaball => *b"MBAL",
+            Self::Bone => *b"BONE",
+            Self::Other(id) => id,
+        }
+    }
+
+    /// Whether the polygons describe a surface, either directly or as a subdivision cage
+    pub fn is_surface(self) -> bool {
+        matches!(self, Self::Face | Self::Patch)
+    }
+}
+
+impl From<[u8; 4]> for PolygonKind {
+    fn from(id: [u8; 4]) -> Self {
+        match &id {
+            b"FACE" => Self::Face,
+            b"CURV" => Self::Curve,
+            b"PTCH" => Self::Patch,
+            b"MBAL" => Self::Metaball,
+            b"BONE" => Self::Bone,
+            _ => Self::Other(id),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+crate::serde_helpers::four_cc_enum!(PolygonKind);
+
 #[binread]
-#[derive(Debug)]
+#[br(import(kind: PolygonKind))]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PolygonList {
     #[br(temp)]
     numvert_and_flags: u16,
+    /// The six high-order bits of the vertex count, including the extra count bits of CURV
+    /// polygons
     #[br(calc = (numvert_and_flags >> 10) as u8)]
     pub flags: u8,
-    #[br(parse_with = count_with_vx((numvert_and_flags & 0x3ff) as usize))]
+    #[br(parse_with = count_with_vx(vertex_count(kind, numvert_and_flags)))]
     pub vert: Vec<u32>,
 }
+
+impl PolygonList {
+    /// The continuity toggles of a CURV polygon, which are stored in the two low-order flag bits
+    pub fn curve_flags(&self) -> CurveFlags {
+        CurveFlags {
+            start_continuity: self.flags & 0b01 != 0,
+            end_continuity: self.flags & 0b10 != 0,
+        }
+    }
+
+    /// The vertex count and flags as they are stored in a POLS chunk of the given kind, or
+    /// `None` if the polygon has more vertices than can be stored, which is 1023 in general and
+    /// 16383 for CURV polygons.
+    ///
+    /// For CURV polygons the four high-order flag bits are replaced by the high bits of the
+    /// vertex count, mirroring how the count is read.
+    pub fn numvert_and_flags(&self, kind: PolygonKind) -> Option<u16> {
+        let count = self.vert.len();
+        match kind {
+            PolygonKind::Curve if count <= 0x3fff => {
+                let count = count as u16;
+                Some((count & 0x3ff) | (count & 0x3c00) << 2 | (self.flags as u16 & 0b11) << 10)
+            }
+            PolygonKind::Curve => None,
+            _ if count <= 0x3ff => Some(count as u16 | (self.flags as u16) << 10),
+            _ => None,
+        }
+    }
+}
+
+/// The flags of a CURV polygon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CurveFlags {
+    /// The first point only controls the tangent at the start, the curve begins at the second
+    pub start_continuity: bool,
+    /// The last point only controls the tangent at the end, the curve ends at the second to last
+    pub end_continuity: bool,
+}
+
+/// CURV polygons use the four high-order flag bits as additional vertex count bits
+fn vertex_count(kind: PolygonKind, numvert_and_flags: u16) -> usize {
+    match kind {
+        PolygonKind::Curve => {
+            ((numvert_and_flags & 0x3ff) | (numvert_and_flags & 0xf000) >> 2) as usize
+        }
+        _ => (numvert_and_flags & 0x3ff) as usize,
+    }
+}
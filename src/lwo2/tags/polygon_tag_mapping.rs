@@ -1,4 +1,5 @@
 use crate::binrw_helpers::until_size_limit;
+use crate::lwo2::string::LwoString;
 use crate::lwo2::vx;
 use binrw::binread;
 
@@ -20,15 +21,135 @@ use binrw::binread;
 /// behavior for polygons lacking a given tag depends on the type.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PolygonTagMappings {
-    pub kind: [u8; 4],
-    #[br(parse_with = until_size_limit(length as u64 - 4))]
+    #[br(map = |id: [u8; 4]| PolygonTagKind::from(id))]
+    pub kind: PolygonTagKind,
+    #[br(parse_with = until_size_limit(length.saturating_sub(4) as u64))]
     pub mappings: Vec<PolygonTagMapping>,
 }
 
+impl PolygonTagMappings {
+    /// The surface name of each polygon, looked up in the tag strings of the most recent TAGS
+    /// chunk. Mappings with an invalid tag index are skipped.
+    pub fn surfaces<'a>(
+        &'a self,
+        tag_strings: &'a [LwoString],
+    ) -> Option<impl Iterator<Item = (u32, &'a LwoString)> + 'a> {
+        self.names_of(PolygonTagKind::Surface, tag_strings)
+    }
+
+    /// The part name of each polygon, see [PolygonTagMappings::surfaces]
+    pub fn parts<'a>(
+        &'a self,
+        tag_strings: &'a [LwoString],
+    ) -> Option<impl Iterator<Item = (u32, &'a LwoString)> + 'a> {
+        self.names_of(PolygonTagKind::Part, tag_strings)
+    }
+
+    /// The smoothing group of each polygon
+    pub fn smoothing_groups(&self) -> Option<impl Iterator<Item = (u32, u16)> + '_> {
+        (self.kind == PolygonTagKind::SmoothingGroup).then(|| {
+            self.mappings
+                .iter()
+                .map(|mapping| (mapping.poly, mapping.tag))
+        })
+    }
+
+    /// The tag string of each polygon, for all kinds whose tags refer to tag strings
+    pub fn names<'a>(
+        &'a self,
+        tag_strings: &'a [LwoString],
+    ) -> Option<impl Iterator<Item = (u32, &'a LwoString)> + 'a> {
+        self.kind.uses_tag_strings().then(|| {
+            self.mappings
+                .iter()
+                .filter_map(|mapping| Some((mapping.poly, tag_strings.get(mapping.tag as usize)?)))
+        })
+    }
+
+    fn names_of<'a>(
+        &'a self,
+        kind: PolygonTagKind,
+        tag_strings: &'a [LwoString],
+    ) -> Option<impl Iterator<Item = (u32, &'a LwoString)> + 'a> {
+        if self.kind == kind {
+            self.names(tag_strings)
+        } else {
+            None
+        }
+    }
+}
+
+/// The type of a PTAG chunk. Unknown types are preserved as [PolygonTagKind::Other].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PolygonTagKind {
+    /// `SURF`, the surface name
+    Surface,
+    /// `PART`, the part name
+    Part,
+    /// `SMGP`, the smoothing group number
+    SmoothingGroup,
+    /// `COLR`, the sketch color used while modeling
+    Color,
+    /// `BONE`, the name of a bone polygon
+    Bone,
+    /// `BNUP`, the up vector of a bone polygon, stored as a tag string
+    BoneUp,
+    /// `BNWT`, the name of the weight map influenced by a bone polygon
+    BoneWeightMap,
+    /// `LXPT`
+    Lxpt,
+    Other([u8; 4]),
+}
+
+impl PolygonTagKind {
+    pub fn id(self) -> [u8; 4] {
+        match self {
+            Self::Surface => *b"SURF",
+            Self::Part => *b"PART",
+            Self::SmoothingGroup => *b"SMGP",
+            Self::Color => *b"COLR",
+            Self::Bone => *b"BONE",
+            Self::BoneUp => *b"BNUP",
+            Self::BoneWeightMap => *b"BNWT",
+            Self::Lxpt => *b"LXPT",
+            Self::Other(id) => id,
+        }
+    }
+
+    /// Whether the tags are indices into the most recent TAGS chunk rather than plain numbers
+    pub fn uses_tag_strings(self) -> bool {
+        matches!(
+            self,
+            Self::Surface | Self::Part | Self::Bone | Self::BoneUp | Self::BoneWeightMap
+        )
+    }
+}
+
+impl From<[u8; 4]> for PolygonTagKind {
+    fn from(id: [u8; 4]) -> Self {
+        match &id {
+            b"SURF" => Self::Surface,
+            b"PART" => Self::Part,
+            b"SMGP" => Self::SmoothingGroup,
+            b"COLR" => Self::Color,
+            b"BONE" => Self::Bone,
+            b"BNUP" => Self::BoneUp,
+            b"BNWT" => Self::BoneWeightMap,
+            b"LXPT" => Self::Lxpt,
+            _ => Self::Other(id),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+crate::serde_helpers::four_cc_enum!(PolygonTagKind);
+
 #[binread]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PolygonTagMapping {
     #[br(parse_with = vx)]
     pub poly: u32,
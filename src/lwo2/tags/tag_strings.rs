@@ -1,11 +1,13 @@
 use crate::binrw_helpers::{lwo_null_string, until_size_limit_with};
+use crate::lwo2::string::LwoString;
 use binrw::binread;
 
 /// Lists the tag strings that can be associated with polygons by the PTAG chunk.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TagStrings {
     #[br(parse_with = until_size_limit_with(length as u64, lwo_null_string))]
-    pub tag_strings: Vec<String>,
+    pub tag_strings: Vec<LwoString>,
 }
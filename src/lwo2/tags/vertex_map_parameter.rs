@@ -3,7 +3,8 @@ use binrw::binread;
 /// Describes special properties of VMAPs.
 #[binread]
 #[br(import(_length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VertexMapParameter {
     pub uv_subdivision_type: UvSubdivisionType,
     pub sketch_color: i32,
@@ -11,7 +12,8 @@ pub struct VertexMapParameter {
 
 #[binread]
 #[br(repr = i32)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UvSubdivisionType {
     Linear = 0,
     Subpatch = 1,
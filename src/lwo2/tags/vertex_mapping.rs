@@ -1,4 +1,5 @@
 use crate::binrw_helpers::{lwo_null_string, until_size_limit};
+use crate::lwo2::string::LwoString;
 use crate::lwo2::vx;
 use binrw::{binread, PosValue};
 
@@ -31,27 +32,176 @@ use binrw::{binread, PosValue};
 /// Other widely used map types will almost certainly appear in the future.
 #[binread]
 #[br(import(length: u32))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VertexMappings {
     #[br(temp)]
     begin_pos: PosValue<()>,
-    pub kind: [u8; 4],
-    #[br(temp)]
-    dimension: u16,
+    #[br(map = |id: [u8; 4]| VertexMapKind::from(id))]
+    pub kind: VertexMapKind,
+    pub dimension: u16,
     #[br(parse_with = lwo_null_string)]
-    pub name: String,
+    pub name: LwoString,
     #[br(temp)]
     end_pos: PosValue<()>,
-    #[br(parse_with = |reader, endian, _: ()| until_size_limit(length as u64 - (end_pos.pos - begin_pos.pos))(reader, endian, (dimension,)))]
+    #[br(parse_with = |reader, endian, _: ()| until_size_limit((length as u64).saturating_sub(end_pos.pos - begin_pos.pos))(reader, endian, (dimension,)))]
     pub mapping: Vec<VertexMapping>,
 }
 
+impl VertexMappings {
+    /// The points of a PICK selection set
+    pub fn as_selection(&self) -> Option<impl Iterator<Item = u32> + '_> {
+        (self.kind == VertexMapKind::Pick).then(|| self.mapping.iter().map(|mapping| mapping.vert))
+    }
+
+    /// The weights of a WGHT or MNVW map
+    pub fn as_weights(&self) -> Option<impl Iterator<Item = (u32, f32)> + '_> {
+        let vectors = match self.kind {
+            VertexMapKind::Weight | VertexMapKind::SubpatchWeight => self.vectors::<1>(),
+            _ => None,
+        }?;
+        Some(vectors.map(|(vert, [weight])| (vert, weight)))
+    }
+
+    /// The coordinates of a TXUV map
+    pub fn as_uv(&self) -> Option<impl Iterator<Item = (u32, [f32; 2])> + '_> {
+        self.vectors_of(VertexMapKind::Uv)
+    }
+
+    /// The colors of an RGB map
+    pub fn as_rgb(&self) -> Option<impl Iterator<Item = (u32, [f32; 3])> + '_> {
+        self.vectors_of(VertexMapKind::Rgb)
+    }
+
+    /// The colors of an RGBA map
+    pub fn as_rgba(&self) -> Option<impl Iterator<Item = (u32, [f32; 4])> + '_> {
+        self.vectors_of(VertexMapKind::Rgba)
+    }
+
+    /// The displacement deltas of a MORF map
+    pub fn as_morph(&self) -> Option<impl Iterator<Item = (u32, [f32; 3])> + '_> {
+        self.vectors_of(VertexMapKind::Morph)
+    }
+
+    /// The absolute positions of a SPOT map
+    pub fn as_positions(&self) -> Option<impl Iterator<Item = (u32, [f32; 3])> + '_> {
+        self.vectors_of(VertexMapKind::AbsoluteMorph)
+    }
+
+    /// The vectors of a NORM map
+    pub fn as_normals(&self) -> Option<impl Iterator<Item = (u32, [f32; 3])> + '_> {
+        self.vectors_of(VertexMapKind::Normal)
+    }
+
+    fn vectors_of<const N: usize>(
+        &self,
+        kind: VertexMapKind,
+    ) -> Option<impl Iterator<Item = (u32, [f32; N])> + '_> {
+        if self.kind == kind {
+            self.vectors()
+        } else {
+            None
+        }
+    }
+
+    /// All vectors, if the map has a dimension of `N`
+    fn vectors<const N: usize>(&self) -> Option<impl Iterator<Item = (u32, [f32; N])> + '_> {
+        (usize::from(self.dimension) == N).then(|| {
+            self.mapping.iter().filter_map(|mapping| {
+                Some((mapping.vert, mapping.value.as_slice().try_into().ok()?))
+            })
+        })
+    }
+}
+
+/// The type of a VMAP or VMAD. Unknown types are preserved as [VertexMapKind::Other].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VertexMapKind {
+    /// `PICK`, a selection set of dimension 0
+    Pick,
+    /// `WGHT`, a weight map of dimension 1
+    Weight,
+    /// `MNVW`, a subpatch weight map of dimension 1
+    SubpatchWeight,
+    /// `TXUV`, a UV texture map of dimension 2
+    Uv,
+    /// `RGB `, a color map of dimension 3
+    Rgb,
+    /// `RGBA`, a color map with alpha of dimension 4
+    Rgba,
+    /// `MORF`, relative vertex displacements of dimension 3
+    Morph,
+    /// `SPOT`, absolute vertex positions of dimension 3
+    AbsoluteMorph,
+    /// `NORM`, vertex normals of dimension 3
+    Normal,
+    /// `MBAL`, metaball parameters
+    Metaball,
+    /// `APSL`
+    Apsl,
+    Other([u8; 4]),
+}
+
+impl VertexMapKind {
+    pub fn id(self) -> [u8; 4] {
+        match self {
+            Self::Pick => *b"PICK",
+            Self::Weight => *b"WGHT",
+            Self::SubpatchWeight => *b"MNVW",
+            Self::Uv => *b"TXUV",
+            Self::Rgb => *b"RGB ",
+            Self::Rgba => *b"RGBA",
+            Self::Morph => *b"MORF",
+            Self::AbsoluteMorph => *b"SPOT",
+            Self::Normal => *b"NORM",
+            Self::Metaball => *b"MBAL",
+            Self::Apsl => *b"APSL",
+            Self::Other(id) => id,
+        }
+    }
+
+    /// The dimension maps of this type are expected to have, if it is known
+    pub fn dimension(self) -> Option<u16> {
+        match self {
+            Self::Pick => Some(0),
+            Self::Weight | Self::SubpatchWeight => Some(1),
+            Self::Uv => Some(2),
+            Self::Rgb | Self::Morph | Self::AbsoluteMorph | Self::Normal => Some(3),
+            Self::Rgba => Some(4),
+            Self::Metaball | Self::Apsl | Self::Other(_) => None,
+        }
+    }
+}
+
+impl From<[u8; 4]> for VertexMapKind {
+    fn from(id: [u8; 4]) -> Self {
+        match &id {
+            b"PICK" => Self::Pick,
+            b"WGHT" => Self::Weight,
+            b"MNVW" => Self::SubpatchWeight,
+            b"TXUV" => Self::Uv,
+            b"RGB " => Self::Rgb,
+            b"RGBA" => Self::Rgba,
+            b"MORF" => Self::Morph,
+            b"SPOT" => Self::AbsoluteMorph,
+            b"NORM" => Self::Normal,
+            b"MBAL" => Self::Metaball,
+            b"APSL" => Self::Apsl,
+            _ => Self::Other(id),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+crate::serde_helpers::four_cc_enum!(VertexMapKind);
+
 #[binread]
 #[br(import(dimension: u16))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VertexMapping {
     #[br(parse_with = vx)]
     pub vert: u32,
-    #[br(count = dimension)]
+    #[br(count = u32::from(dimension))]
     pub value: Vec<f32>,
 }
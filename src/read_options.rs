@@ -0,0 +1,310 @@
+use binrw::{BinRead, BinResult, Endian};
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Limits that are enforced while reading, so that untrusted input can't make the parser
+/// allocate more memory than intended.
+///
+/// Independent of these limits, a chunk can never claim to be larger than the chunk it is
+/// contained in or the remaining data in the stream.
+#[derive(Debug, Clone, Copy)]
+pub struct ReadOptions {
+    /// The maximum file size in bytes, as declared by the `FORM` header.
+    pub max_file_size: u64,
+    /// The maximum number of points in a single PNTS chunk.
+    pub max_points: usize,
+    /// The maximum number of polygons in a single POLS chunk.
+    pub max_polygons: usize,
+    /// The maximum length of a single string in bytes.
+    pub max_string_length: usize,
+    /// The maximum nesting depth of chunks and subchunks.
+    pub max_chunk_depth: usize,
+    /// The decoder used for all strings. The raw bytes are kept either way.
+    pub string_decoder: StringDecoder,
+}
+
+/// Decodes the raw bytes of strings, which the spec defines as ASCII but are in practice
+/// written in whatever code page the authoring system used.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum StringDecoder {
+    /// Invalid sequences are replaced with U+FFFD
+    #[default]
+    Utf8,
+    /// ISO-8859-1, as written by most European installations
+    Latin1,
+    /// Shift-JIS, as written by Japanese installations
+    #[cfg(feature = "encoding")]
+    ShiftJis,
+    Custom(fn(&[u8]) -> String),
+}
+
+impl StringDecoder {
+    pub fn decode(self, bytes: &[u8]) -> String {
+        match self {
+            Self::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Self::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+            #[cfg(feature = "encoding")]
+            Self::ShiftJis => encoding_rs::SHIFT_JIS
+                .decode_without_bom_handling(bytes)
+                .0
+                .into_owned(),
+            Self::Custom(decode) => decode(bytes),
+        }
+    }
+}
+
+impl Default for ReadOptions {
+    /// The defaults are the limits of the format itself, VX indices for example can't address
+    /// more than 2^24 points or polygons.
+    fn default() -> Self {
+        Self {
+            max_file_size: u32::MAX as u64 + 8,
+            max_points: 1 << 24,
+            max_polygons: 1 << 24,
+            max_string_length: u16::MAX as usize,
+            max_chunk_depth: 16,
+            string_decoder: StringDecoder::default(),
+        }
+    }
+}
+
+/// The error returned as [binrw::Error::Custom] when a limit of [ReadOptions] is exceeded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    FileSize {
+        size: u64,
+        max: u64,
+    },
+    Points {
+        count: usize,
+        max: usize,
+    },
+    Polygons {
+        count: usize,
+        max: usize,
+    },
+    StringLength {
+        max: usize,
+    },
+    ChunkDepth {
+        max: usize,
+    },
+    /// A chunk is larger than the remaining space in its parent chunk or stream.
+    ChunkLength {
+        length: u64,
+        available: u64,
+    },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileSize { size, max } => {
+                write!(
+                    f,
+                    "file size of {} bytes exceeds the limit of {}",
+                    size, max
+                )
+            }
+            Self::Points { count, max } => {
+                write!(f, "{} points exceed the limit of {}", count, max)
+            }
+            Self::Polygons { count, max } => {
+                write!(f, "{} polygons exceed the limit of {}", count, max)
+            }
+            Self::StringLength { max } => write!(f, "string exceeds the limit of {} bytes", max),
+            Self::ChunkDepth { max } => write!(f, "chunks are nested deeper than {}", max),
+            Self::ChunkLength { length, available } => write!(
+                f,
+                "chunk length of {} bytes exceeds the {} bytes available",
+                length, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl LimitError {
+    /// Finds the limit that caused a read to fail. Since chunks are read by trying every known
+    /// chunk type, the actual error is usually buried in backtraces and per-variant errors.
+    pub fn find(error: &binrw::Error) -> Option<&LimitError> {
+        match error {
+            binrw::Error::Custom { .. } => error.custom_err(),
+            binrw::Error::Backtrace(backtrace) => Self::find(&backtrace.error),
+            binrw::Error::EnumErrors { variant_errors, .. } => variant_errors
+                .iter()
+                .find_map(|(_, error)| Self::find(error)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn at(self, pos: u64) -> binrw::Error {
+        binrw::Error::Custom {
+            pos,
+            err: Box::new(self),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ReadState {
+    options: ReadOptions,
+    /// The end positions of all chunks that are currently being read, innermost last
+    scope_ends: Vec<u64>,
+}
+
+thread_local! {
+    static STATE: RefCell<ReadState> = RefCell::new(ReadState::default());
+}
+
+/// Runs `read` with `options` applied to all chunks and strings read on this thread.
+pub(crate) fn with_read_options<T>(options: &ReadOptions, read: impl FnOnce() -> T) -> T {
+    let previous = STATE.with(|state| {
+        std::mem::replace(
+            &mut *state.borrow_mut(),
+            ReadState {
+                options: *options,
+                scope_ends: vec![],
+            },
+        )
+    });
+    let _restore = RestoreState(Some(previous));
+    read()
+}
+
+/// Puts back the state that was active before [with_read_options], even if the read panics.
+struct RestoreState(Option<ReadState>);
+
+impl Drop for RestoreState {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            STATE.with(|state| *state.borrow_mut() = previous);
+        }
+    }
+}
+
+pub(crate) fn read_options() -> ReadOptions {
+    STATE.with(|state| state.borrow().options)
+}
+
+/// Reads the length of a `FORM`, checking the resulting file size against the limit.
+pub(crate) fn form_length<R>(reader: &mut R, endian: Endian, _args: ()) -> BinResult<u32>
+where
+    R: Read + Seek,
+{
+    let pos = reader.stream_position()?;
+    let length = u32::read_options(reader, endian, ())?;
+    let size = length as u64 + 8;
+    let max = read_options().max_file_size;
+    if size > max {
+        Err(LimitError::FileSize { size, max }.at(pos))
+    } else {
+        Ok(length)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum CountLimit {
+    Points,
+    Polygons,
+}
+
+impl CountLimit {
+    pub(crate) fn check(self, pos: u64, count: usize) -> BinResult<()> {
+        let options = read_options();
+        match self {
+            Self::Points if count > options.max_points => Err(LimitError::Points {
+                count,
+                max: options.max_points,
+            }
+            .at(pos)),
+            Self::Polygons if count > options.max_polygons => Err(LimitError::Polygons {
+                count,
+                max: options.max_polygons,
+            }
+            .at(pos)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reads the data of a chunk or subchunk, making sure it fits into its parent and doesn't
+/// exceed the maximum nesting depth.
+pub(crate) fn chunk_data<R, D>(
+    length: u32,
+) -> impl Fn(&mut R, Endian, (u32,)) -> BinResult<D> + Copy
+where
+    R: Read + Seek,
+    for<'a> D: BinRead<Args<'a> = (u32,)>,
+{
+    move |reader, endian, args| {
+        read_scoped(reader, length as u64, |reader| {
+            let mut value = D::read_options(reader, endian, args)?;
+            value.after_parse(reader, endian, args)?;
+            Ok(value)
+        })
+    }
+}
+
+/// Runs `read` for `length` bytes of chunk data starting at the current position, after
+/// checking that they fit into the enclosing chunk (or the stream) and that the maximum
+/// nesting depth isn't exceeded.
+pub(crate) fn read_scoped<R, T>(
+    reader: &mut R,
+    length: u64,
+    read: impl FnOnce(&mut R) -> BinResult<T>,
+) -> BinResult<T>
+where
+    R: Read + Seek,
+{
+    let pos = reader.stream_position()?;
+    let parent_end = match STATE.with(|state| state.borrow().scope_ends.last().copied()) {
+        Some(end) => end,
+        None => {
+            let end = reader.seek(SeekFrom::End(0))?;
+            reader.seek(SeekFrom::Start(pos))?;
+            end
+        }
+    };
+    let available = parent_end.saturating_sub(pos);
+    if length > available {
+        return Err(LimitError::ChunkLength { length, available }.at(pos));
+    }
+
+    let max_depth = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.scope_ends.push(pos + length);
+        (state.scope_ends.len() > state.options.max_chunk_depth)
+            .then_some(state.options.max_chunk_depth)
+    });
+    let result = match max_depth {
+        Some(max) => Err(LimitError::ChunkDepth { max }.at(pos)),
+        None => read(reader),
+    };
+    STATE.with(|state| state.borrow_mut().scope_ends.pop());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_read_options_restores_state_after_panic() {
+        let options = ReadOptions {
+            max_points: 1,
+            ..ReadOptions::default()
+        };
+        let result = std::panic::catch_unwind(|| {
+            with_read_options(&options, || {
+                STATE.with(|state| state.borrow_mut().scope_ends.push(8));
+                panic!("read failed");
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!(read_options().max_points, ReadOptions::default().max_points);
+        assert!(STATE.with(|state| state.borrow().scope_ends.is_empty()));
+    }
+}
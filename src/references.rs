@@ -0,0 +1,210 @@
+//! The references surfaces and clips make to clips, envelopes and other surfaces.
+//!
+//! The referencing fields are listed once and expanded into both a [Visitor] and a
+//! [VisitorMut], so that [LightWaveObject::validate](crate::LightWaveObject::validate) and the
+//! splitting and merging of objects agree on what counts as a reference.
+
+use crate::lwo2::string::LwoString;
+use crate::lwo2::sub_tags::blocks::image_texture::SurfaceBlockImageTextureSubChunk;
+use crate::lwo2::sub_tags::blocks::texture_mapping::TextureMappingSubChunk;
+use crate::lwo2::sub_tags::blocks::SurfaceBlockHeaderSubChunk;
+use crate::lwo2::sub_tags::surface_parameters::SurfaceParameterSubChunk;
+use crate::lwo2::tags::image_clip::ImageClipSubChunk;
+use crate::lwo2::tags::surface_definition::SurfaceDefinition;
+use crate::visit::{
+    walk_image_texture_attribute, walk_image_texture_attribute_mut, walk_surface_definition,
+    walk_surface_definition_mut, walk_surface_parameter, walk_surface_parameter_mut, Visitor,
+    VisitorMut,
+};
+
+/// Receives the references found by [References]. The path is the chunk ID followed by the IDs
+/// of all subchunks leading to the reference, for example `["SURF", "BLOK", "IMAG"]`.
+pub(crate) trait ReferenceSink {
+    fn clip(&mut self, _path: &'static [&'static str], _index: &u32) {}
+
+    fn envelope(&mut self, _path: &'static [&'static str], _index: &u32) {}
+
+    /// The surface a SURF chunk is derived from
+    fn surface(&mut self, _path: &'static [&'static str], _name: &LwoString) {}
+}
+
+/// Like [ReferenceSink], but with mutable access to the references found by [ReferencesMut].
+pub(crate) trait ReferenceSinkMut {
+    fn clip(&mut self, _path: &'static [&'static str], _index: &mut u32) {}
+
+    fn envelope(&mut self, _path: &'static [&'static str], _index: &mut u32) {}
+
+    fn surface(&mut self, _path: &'static [&'static str], _name: &mut LwoString) {}
+}
+
+/// Passes every reference of the visited surfaces and clips to a [ReferenceSink]. Indices of
+/// zero and empty surface names mean "none" and are skipped.
+pub(crate) struct References<'a, S: ?Sized>(pub &'a mut S);
+
+/// Like [References], but passes mutable references to a [ReferenceSinkMut].
+pub(crate) struct ReferencesMut<'a, S: ?Sized>(pub &'a mut S);
+
+macro_rules! references {
+    (
+        $name:ident: $visitor:ident for $sink:ident,
+        $visit_surface_definition:ident => $walk_surface_definition:ident,
+        $visit_surface_parameter:ident => $walk_surface_parameter:ident,
+        $visit_surface_block_header_attribute:ident,
+        $visit_image_texture_attribute:ident => $walk_image_texture_attribute:ident,
+        $visit_texture_mapping_attribute:ident,
+        $visit_image_clip_attribute:ident,
+        $($mutability:tt)?
+    ) => {
+        impl<S: $sink + ?Sized> $name<'_, S> {
+            fn clip(&mut self, path: &'static [&'static str], index: &$($mutability)? u32) {
+                if *index != 0 {
+                    self.0.clip(path, index)
+                }
+            }
+
+            fn envelope(&mut self, path: &'static [&'static str], index: &$($mutability)? u32) {
+                if *index != 0 {
+                    self.0.envelope(path, index)
+                }
+            }
+        }
+
+        impl<S: $sink + ?Sized> $visitor for $name<'_, S> {
+            fn $visit_surface_definition(
+                &mut self,
+                surface: &$($mutability)? SurfaceDefinition,
+            ) {
+                if !surface.source.as_str().is_empty() {
+                    self.0.surface(&["SURF"], &$($mutability)? surface.source)
+                }
+                $walk_surface_definition(self, surface)
+            }
+
+            fn $visit_surface_parameter(
+                &mut self,
+                attribute: &$($mutability)? SurfaceParameterSubChunk,
+            ) {
+                use SurfaceParameterSubChunk::*;
+                match attribute {
+                    BaseColor(value) => self.envelope(&["SURF", "COLR"], &$($mutability)? value.envelope),
+                    BaseShadingValueDiffuse(value) => self.envelope(&["SURF", "DIFF"], &$($mutability)? value.envelope),
+                    BaseShadingValueLuminosity(value) => self.envelope(&["SURF", "LUMI"], &$($mutability)? value.envelope),
+                    BaseShadingValueSpecular(value) => self.envelope(&["SURF", "SPEC"], &$($mutability)? value.envelope),
+                    BaseShadingValueReflectivity(value) => self.envelope(&["SURF", "REFL"], &$($mutability)? value.envelope),
+                    BaseShadingValueTransparency(value) => self.envelope(&["SURF", "TRAN"], &$($mutability)? value.envelope),
+                    BaseShadingValueTranslucency(value) => self.envelope(&["SURF", "TRNL"], &$($mutability)? value.envelope),
+                    SpecularGlossiness(value) => self.envelope(&["SURF", "GLOS"], &$($mutability)? value.envelope),
+                    DiffuseSharpness(value) => self.envelope(&["SURF", "SHRP"], &$($mutability)? value.envelope),
+                    BumpIntensity(value) => self.envelope(&["SURF", "BUMP"], &$($mutability)? value.envelope),
+                    ReflectionMapImage(image) => self.clip(&["SURF", "RIMG"], &$($mutability)? image.texture_image),
+                    ReflectionMapSeamAngle(value) => self.envelope(&["SURF", "RSAN"], &$($mutability)? value.envelope),
+                    ReflectionBlurring(value) => self.envelope(&["SURF", "RBLR"], &$($mutability)? value.envelope),
+                    RefractiveIndex(value) => self.envelope(&["SURF", "RIND"], &$($mutability)? value.envelope),
+                    RefractionMapImage(image) => self.clip(&["SURF", "TIMG"], &$($mutability)? image.texture_image),
+                    RefractionBlurring(value) => self.envelope(&["SURF", "TBLR"], &$($mutability)? value.envelope),
+                    ColorHighlights(value) => self.envelope(&["SURF", "CLRH"], &$($mutability)? value.envelope),
+                    ColorFilter(value) => self.envelope(&["SURF", "CLRF"], &$($mutability)? value.envelope),
+                    AdditiveTransparency(value) => self.envelope(&["SURF", "ADTR"], &$($mutability)? value.envelope),
+                    GlowEffect(glow) => {
+                        self.envelope(&["SURF", "GLOW"], &$($mutability)? glow.intensity_envelope);
+                        self.envelope(&["SURF", "GLOW"], &$($mutability)? glow.size_envelope);
+                    }
+                    RenderOutlines(outlines) => {
+                        self.envelope(&["SURF", "LINE"], &$($mutability)? outlines.size_envelope);
+                        self.envelope(&["SURF", "LINE"], &$($mutability)? outlines.color_envelope);
+                    }
+                    VertexColorMap(map) => self.envelope(&["SURF", "VCOL"], &$($mutability)? map.envelope),
+                    Blocks(_)
+                    | PolygonSidedness(_)
+                    | MaxSmoothingAngle(_)
+                    | ReflectionOptions(_)
+                    | TransparencyOptions(_)
+                    | AlphaMode(_) => (),
+                }
+                $walk_surface_parameter(self, attribute)
+            }
+
+            fn $visit_surface_block_header_attribute(
+                &mut self,
+                attribute: &$($mutability)? SurfaceBlockHeaderSubChunk,
+            ) {
+                if let SurfaceBlockHeaderSubChunk::Opacity(opacity) = attribute {
+                    self.envelope(&["SURF", "BLOK", "OPAC"], &$($mutability)? opacity.envelope)
+                }
+            }
+
+            fn $visit_image_texture_attribute(
+                &mut self,
+                attribute: &$($mutability)? SurfaceBlockImageTextureSubChunk,
+            ) {
+                use SurfaceBlockImageTextureSubChunk::*;
+                match attribute {
+                    ImageMap(image) => self.clip(&["SURF", "BLOK", "IMAG"], &$($mutability)? image.texture_image),
+                    ImageWrapAmountWidth(amount) => self.envelope(&["SURF", "BLOK", "WRPW"], &$($mutability)? amount.envelope),
+                    ImageWrapAmountHeight(amount) => self.envelope(&["SURF", "BLOK", "WRPH"], &$($mutability)? amount.envelope),
+                    StickyProjection(value) => self.envelope(&["SURF", "BLOK", "STCK"], &$($mutability)? value.envelope),
+                    TextureAmplitude(value) => self.envelope(&["SURF", "BLOK", "TAMP"], &$($mutability)? value.envelope),
+                    TextureMapping(_)
+                    | ProjectionMode(_)
+                    | MajorAxis(_)
+                    | ImageWrapOptions(_)
+                    | UvVertexMap(_)
+                    | AntialiasingStrength(_)
+                    | PixelBlending(_) => (),
+                }
+                $walk_image_texture_attribute(self, attribute)
+            }
+
+            fn $visit_texture_mapping_attribute(
+                &mut self,
+                attribute: &$($mutability)? TextureMappingSubChunk,
+            ) {
+                use TextureMappingSubChunk::*;
+                match attribute {
+                    Center(value) => self.envelope(&["SURF", "BLOK", "TMAP", "CNTR"], &$($mutability)? value.envelope),
+                    Size(value) => self.envelope(&["SURF", "BLOK", "TMAP", "SIZE"], &$($mutability)? value.envelope),
+                    Rotation(value) => self.envelope(&["SURF", "BLOK", "TMAP", "ROTA"], &$($mutability)? value.envelope),
+                    Falloff(value) => self.envelope(&["SURF", "BLOK", "TMAP", "FALL"], &$($mutability)? value.envelope),
+                    ReferenceObject(_) | CoordinateSystem(_) => (),
+                }
+            }
+
+            fn $visit_image_clip_attribute(
+                &mut self,
+                attribute: &$($mutability)? ImageClipSubChunk,
+            ) {
+                use ImageClipSubChunk::*;
+                match attribute {
+                    Reference(reference) => self.clip(&["CLIP", "XREF"], &$($mutability)? reference.index),
+                    Contrast(value) => self.envelope(&["CLIP", "CONT"], &$($mutability)? value.envelope),
+                    Brightness(value) => self.envelope(&["CLIP", "BRIT"], &$($mutability)? value.envelope),
+                    Saturation(value) => self.envelope(&["CLIP", "SATR"], &$($mutability)? value.envelope),
+                    Hue(value) => self.envelope(&["CLIP", "HUE"], &$($mutability)? value.envelope),
+                    GammaCorrection(value) => self.envelope(&["CLIP", "GAMM"], &$($mutability)? value.envelope),
+                    _ => (),
+                }
+            }
+        }
+    };
+}
+
+references!(
+    References: Visitor for ReferenceSink,
+    visit_surface_definition => walk_surface_definition,
+    visit_surface_parameter => walk_surface_parameter,
+    visit_surface_block_header_attribute,
+    visit_image_texture_attribute => walk_image_texture_attribute,
+    visit_texture_mapping_attribute,
+    visit_image_clip_attribute,
+);
+
+references!(
+    ReferencesMut: VisitorMut for ReferenceSinkMut,
+    visit_surface_definition_mut => walk_surface_definition_mut,
+    visit_surface_parameter_mut => walk_surface_parameter_mut,
+    visit_surface_block_header_attribute_mut,
+    visit_image_texture_attribute_mut => walk_image_texture_attribute_mut,
+    visit_texture_mapping_attribute_mut,
+    visit_image_clip_attribute_mut,
+    mut
+);
@@ -0,0 +1,98 @@
+/// Serializes four-character IDs as strings instead of byte arrays. IDs shorter than four
+/// characters are padded with null bytes, which are stripped again when serializing.
+///
+/// IDs that cannot be written as such a string without losing information, because they
+/// contain bytes outside of printable ASCII or a null byte followed by anything else, are
+/// serialized as an array of their four bytes instead.
+pub mod four_cc {
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+    use std::fmt;
+
+    pub fn serialize<S>(id: &[u8; 4], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let length = id.iter().position(|&b| b == 0).unwrap_or(id.len());
+        if id[..length]
+            .iter()
+            .all(|b| b.is_ascii_graphic() || *b == b' ')
+            && id[length..].iter().all(|&b| b == 0)
+        {
+            serializer.serialize_str(std::str::from_utf8(&id[..length]).unwrap())
+        } else {
+            serializer.collect_seq(id)
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<[u8; 4], D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(FourCcVisitor)
+    }
+
+    struct FourCcVisitor;
+
+    impl<'de> Visitor<'de> for FourCcVisitor {
+        type Value = [u8; 4];
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a four-character ID or an array of four bytes")
+        }
+
+        fn visit_str<E>(self, id: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            if id.len() > 4 || !id.bytes().all(|b| b.is_ascii_graphic() || b == b' ') {
+                return Err(E::custom(format!("\"{}\" is not a four-character ID", id)));
+            }
+            let mut result = [0u8; 4];
+            result[..id.len()].copy_from_slice(id.as_bytes());
+            Ok(result)
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut result = [0u8; 4];
+            for (index, byte) in result.iter_mut().enumerate() {
+                *byte = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(index, &self))?;
+            }
+            if seq.next_element::<u8>()?.is_some() {
+                return Err(A::Error::invalid_length(5, &self));
+            }
+            Ok(result)
+        }
+    }
+}
+
+/// Implements `Serialize` and `Deserialize` for enums of four-character IDs, which provide
+/// `id()` and `From<[u8; 4]>`, so that they are serialized the same way as raw IDs.
+macro_rules! four_cc_enum {
+    ($kind:ty) => {
+        impl serde::Serialize for $kind {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                crate::serde_helpers::four_cc::serialize(&self.id(), serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $kind {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                crate::serde_helpers::four_cc::deserialize(deserializer).map(<$kind>::from)
+            }
+        }
+    };
+}
+
+pub(crate) use four_cc_enum;
@@ -0,0 +1,423 @@
+//! Splitting objects into one object per layer, and merging objects as the layers of one object.
+//!
+//! Points, polygons and vertex maps only refer to data within their own layer and are copied
+//! as they are. Everything that is shared between layers is re-indexed: tag strings (and with
+//! them the surfaces polygons are assigned to), clips and envelopes.
+//!
+//! Clips and envelopes are referred to by VX indices, which take two bytes below 0xFF00 and four
+//! bytes otherwise. New indices are counted from 1 for two-byte indices and from 0xFF00 for
+//! four-byte ones, so that no reference changes its size and the lengths of the chunks and
+//! subchunks around it stay valid.
+
+use crate::chunk_length::{
+    form_length, layer_length, polygon_tag_mappings_length, tag_strings_length, vx_length,
+};
+use crate::iff::Chunk;
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::layer::Layer;
+use crate::lwo2::tags::polygon_tag_mapping::{PolygonTagKind, PolygonTagMappings};
+use crate::lwo2::tags::tag_strings::TagStrings;
+use crate::lwo2::tags::Tag;
+use crate::references::{ReferenceSink, ReferenceSinkMut, References, ReferencesMut};
+use crate::visit::{Visitor, VisitorMut};
+use crate::LightWaveObject;
+use std::collections::{HashMap, HashSet};
+
+impl LightWaveObject {
+    /// Copies the first layer with the given number into an object of its own.
+    ///
+    /// The new object only contains the tag strings used by the layer's polygon tags, the
+    /// surfaces assigned to its polygons (along with the surfaces they are derived from) and
+    /// the clips and envelopes those surfaces use, which are renumbered in file order.
+    /// Description lines, commentary and icons are kept, and the layer loses its parent.
+    ///
+    /// The layer's points are copied as they are, including points that no polygon uses, since
+    /// those can be meaningful on their own, for example as a point cloud.
+    /// [LightWaveObject::remove_unused_points] removes them.
+    pub fn extract_layer(&self, number: u16) -> Option<LightWaveObject> {
+        let start = self
+            .data
+            .iter()
+            .position(|tag| matches!(tag, Tag::Layer(layer) if layer.number == number))?;
+        let end = self.data[start + 1..]
+            .iter()
+            .position(|tag| matches!(tag, Tag::Layer(_)))
+            .map_or(self.data.len(), |end| start + 1 + end);
+
+        let mut tag_strings = TagStringMerger::default();
+        let mut current_tag_strings: &[LwoString] = &[];
+        let mut layer_data = vec![];
+        for (index, tag) in self.data[..end].iter().enumerate() {
+            match tag {
+                Tag::TagStrings(chunk) => current_tag_strings = &chunk.tag_strings,
+                Tag::PolygonTagMapping(chunk) if index > start => layer_data.push(
+                    Tag::PolygonTagMapping(tag_strings.remap(chunk, current_tag_strings)),
+                ),
+                _ if index > start && is_layer_data(tag) => layer_data.push(tag.clone()),
+                _ => (),
+            }
+        }
+
+        let surfaces = self.surfaces_with_sources(tag_strings.surfaces.clone());
+        let (clips, envelopes) = self.references_of(&surfaces);
+        let mut data = vec![];
+        for (index, tag) in self.data.iter().enumerate() {
+            match tag {
+                Tag::DescriptionLine(_) | Tag::CommentaryText(_) | Tag::ThumbnailIconImage(_) => {
+                    data.push(tag.clone())
+                }
+                Tag::Layer(chunk) if index == start => {
+                    if !tag_strings.tag_strings.is_empty() {
+                        data.push(tag_strings.chunk());
+                    }
+                    data.push(Tag::Layer(layer_chunk(Layer {
+                        parent: None,
+                        ..chunk.data.clone()
+                    })));
+                    data.append(&mut layer_data);
+                }
+                Tag::ImageClip(chunk) => {
+                    if let Some(&index) = clips.get(&chunk.index) {
+                        let mut chunk = chunk.clone();
+                        chunk.index = index;
+                        data.push(Tag::ImageClip(chunk));
+                    }
+                }
+                Tag::EnvelopeDefinition(chunk) => {
+                    if let Some(&index) = envelopes.get(&chunk.index) {
+                        let mut chunk = chunk.clone();
+                        chunk.index = index;
+                        data.push(Tag::EnvelopeDefinition(chunk));
+                    }
+                }
+                Tag::SurfaceDefinition(chunk) if surfaces.contains(chunk.name.as_str()) => {
+                    data.push(tag.clone())
+                }
+                _ => (),
+            }
+        }
+
+        remap_references(&mut data, &clips, &envelopes);
+        Some(LightWaveObject {
+            file_size: form_length(&data),
+            data,
+        })
+    }
+
+    /// Merges several objects into one, with the layers of every object following the layers of
+    /// the objects before it.
+    ///
+    /// Layers are numbered from 0 in order, and parents are remapped to the new numbers of the
+    /// layers within the same object. Data before the first layer of an object gets a layer of
+    /// its own. All tag strings are merged into a single TAGS chunk, and clips and envelopes are
+    /// renumbered in order. Surfaces are identified by their name, so a surface defined by
+    /// several objects keeps its first definition.
+    ///
+    /// # Panics
+    ///
+    /// If the objects have more than 65,279 clips or envelopes with two-byte indices together,
+    /// which can't be renumbered without changing the size of their references.
+    pub fn merge_layers(objects: &[LightWaveObject]) -> LightWaveObject {
+        let mut tag_strings = TagStringMerger::default();
+        let mut surfaces = HashSet::new();
+        let mut layer_count = 0u16;
+        let mut clip_numbers = Renumbering::default();
+        let mut envelope_numbers = Renumbering::default();
+        let mut data = vec![];
+
+        for object in objects {
+            let mut layers = HashMap::new();
+            let mut clips = HashMap::new();
+            let mut envelopes = HashMap::new();
+            // the number of the layer for the data before the first LAYR
+            let mut first_layer = None;
+            for tag in &object.data {
+                match tag {
+                    Tag::Layer(chunk) => {
+                        layers.entry(chunk.number).or_insert_with(|| {
+                            layer_count += 1;
+                            layer_count - 1
+                        });
+                    }
+                    _ if layers.is_empty() && first_layer.is_none() && is_layer_data(tag) => {
+                        first_layer = Some(layer_count);
+                        layer_count += 1;
+                    }
+                    Tag::ImageClip(chunk) => {
+                        clips
+                            .entry(chunk.index)
+                            .or_insert_with(|| clip_numbers.next(chunk.index));
+                    }
+                    Tag::EnvelopeDefinition(chunk) => {
+                        envelopes
+                            .entry(chunk.index)
+                            .or_insert_with(|| envelope_numbers.next(chunk.index));
+                    }
+                    _ => (),
+                }
+            }
+
+            let mut object_data = vec![];
+            let mut current_tag_strings: &[LwoString] = &[];
+            for tag in &object.data {
+                match tag {
+                    Tag::TagStrings(chunk) => current_tag_strings = &chunk.tag_strings,
+                    Tag::Layer(chunk) => {
+                        let parent = chunk
+                            .parent_number()
+                            .and_then(|parent| layers.get(&parent).copied());
+                        object_data.push(Tag::Layer(layer_chunk(Layer {
+                            number: layers[&chunk.number],
+                            parent,
+                            ..chunk.data.clone()
+                        })));
+                    }
+                    Tag::ImageClip(chunk) => {
+                        let mut chunk = chunk.clone();
+                        chunk.index = clips[&chunk.index];
+                        object_data.push(Tag::ImageClip(chunk));
+                    }
+                    Tag::EnvelopeDefinition(chunk) => {
+                        let mut chunk = chunk.clone();
+                        chunk.index = envelopes[&chunk.index];
+                        object_data.push(Tag::EnvelopeDefinition(chunk));
+                    }
+                    Tag::SurfaceDefinition(chunk) => {
+                        if surfaces.insert(chunk.name.clone()) {
+                            object_data.push(tag.clone());
+                        }
+                    }
+                    _ if is_layer_data(tag) => {
+                        if let Some(number) = first_layer.take() {
+                            object_data.push(Tag::Layer(layer_chunk(Layer {
+                                number,
+                                flags: 0,
+                                pivot: [0.0; 3],
+                                name: LwoString::default(),
+                                parent: None,
+                            })));
+                        }
+                        object_data.push(match tag {
+                            Tag::PolygonTagMapping(chunk) => Tag::PolygonTagMapping(
+                                tag_strings.remap(chunk, current_tag_strings),
+                            ),
+                            _ => tag.clone(),
+                        });
+                    }
+                    _ => object_data.push(tag.clone()),
+                }
+            }
+            remap_references(&mut object_data, &clips, &envelopes);
+            data.append(&mut object_data);
+        }
+
+        if !tag_strings.tag_strings.is_empty() {
+            data.insert(0, tag_strings.chunk());
+        }
+        LightWaveObject {
+            file_size: form_length(&data),
+            data,
+        }
+    }
+
+    /// The given surfaces along with all surfaces they are derived from
+    fn surfaces_with_sources(&self, mut surfaces: HashSet<String>) -> HashSet<String> {
+        loop {
+            let sources: Vec<String> = self
+                .data
+                .iter()
+                .filter_map(|tag| match tag {
+                    Tag::SurfaceDefinition(chunk) if surfaces.contains(chunk.name.as_str()) => {
+                        Some(chunk.source.to_string())
+                    }
+                    _ => None,
+                })
+                .filter(|source| !source.is_empty() && !surfaces.contains(source))
+                .collect();
+            if sources.is_empty() {
+                return surfaces;
+            }
+            surfaces.extend(sources);
+        }
+    }
+
+    /// The clips and envelopes used by the given surfaces and the clips they use, mapped to new
+    /// indices in file order
+    fn references_of(&self, surfaces: &HashSet<String>) -> (HashMap<u32, u32>, HashMap<u32, u32>) {
+        let mut used = UsedReferences::default();
+        for tag in &self.data {
+            if matches!(tag, Tag::SurfaceDefinition(chunk) if surfaces.contains(chunk.name.as_str()))
+            {
+                References(&mut used).visit_tag(tag);
+            }
+        }
+        // clips can refer to other clips, which can refer to envelopes in turn
+        let mut visited = HashSet::new();
+        while let Some(&clip) = used.clips.difference(&visited).next() {
+            visited.insert(clip);
+            for tag in &self.data {
+                if matches!(tag, Tag::ImageClip(chunk) if chunk.index == clip) {
+                    References(&mut used).visit_tag(tag);
+                }
+            }
+        }
+
+        // a single object can't have more two-byte indices than fit into two bytes
+        let mut clip_numbers = Renumbering::default();
+        let mut envelope_numbers = Renumbering::default();
+        let mut new_clips = HashMap::new();
+        let mut new_envelopes = HashMap::new();
+        for tag in &self.data {
+            match tag {
+                Tag::ImageClip(chunk) if used.clips.contains(&chunk.index) => {
+                    new_clips
+                        .entry(chunk.index)
+                        .or_insert_with(|| clip_numbers.next(chunk.index));
+                }
+                Tag::EnvelopeDefinition(chunk) if used.envelopes.contains(&chunk.index) => {
+                    new_envelopes
+                        .entry(chunk.index)
+                        .or_insert_with(|| envelope_numbers.next(chunk.index));
+                }
+                _ => (),
+            }
+        }
+        (new_clips, new_envelopes)
+    }
+}
+
+/// Assigns new clip or envelope indices in order, keeping the size of every index as a VX
+#[derive(Default)]
+struct Renumbering {
+    two_byte: u32,
+    four_byte: u32,
+}
+
+impl Renumbering {
+    fn next(&mut self, index: u32) -> u32 {
+        if vx_length(index) == 2 {
+            self.two_byte += 1;
+            assert!(
+                self.two_byte < 0xff00,
+                "too many clips or envelopes with two-byte indices"
+            );
+            self.two_byte
+        } else {
+            self.four_byte += 1;
+            0xff00 + self.four_byte - 1
+        }
+    }
+}
+
+/// Collects tag strings from several TAGS chunks into one, without duplicates
+#[derive(Default)]
+struct TagStringMerger {
+    tag_strings: Vec<LwoString>,
+    /// The names of all surfaces assigned by the remapped polygon tags
+    surfaces: HashSet<String>,
+}
+
+impl TagStringMerger {
+    /// Rewrites the tag indices of a PTAG chunk to refer to the merged tag strings. Mappings
+    /// with an invalid tag index are dropped.
+    fn remap(
+        &mut self,
+        chunk: &Chunk<PolygonTagMappings>,
+        tag_strings: &[LwoString],
+    ) -> Chunk<PolygonTagMappings> {
+        let mut mappings = chunk.data.clone();
+        if mappings.kind.uses_tag_strings() {
+            mappings.mappings.retain_mut(|mapping| {
+                let Some(name) = tag_strings.get(mapping.tag as usize) else {
+                    return false;
+                };
+                if mappings.kind == PolygonTagKind::Surface {
+                    self.surfaces.insert(name.to_string());
+                }
+                let index = self.tag_strings.iter().position(|tag| tag == name);
+                mapping.tag = index.unwrap_or_else(|| {
+                    self.tag_strings.push(name.clone());
+                    self.tag_strings.len() - 1
+                }) as u16;
+                true
+            });
+        }
+        Chunk {
+            length: polygon_tag_mappings_length(&mappings),
+            data: mappings,
+        }
+    }
+
+    fn chunk(&self) -> Tag {
+        let data = TagStrings {
+            tag_strings: self.tag_strings.clone(),
+        };
+        Tag::TagStrings(Chunk {
+            length: tag_strings_length(&data),
+            data,
+        })
+    }
+}
+
+/// Whether a chunk belongs to the layer it appears in
+fn is_layer_data(tag: &Tag) -> bool {
+    matches!(
+        tag,
+        Tag::PointList(_)
+            | Tag::VertexMapping(_)
+            | Tag::PolygonList(_)
+            | Tag::PolygonTagMapping(_)
+            | Tag::DiscontinuousVertexMapping(_)
+            | Tag::VertexMapParameter(_)
+            | Tag::BoundingBox(_)
+    )
+}
+
+fn layer_chunk(layer: Layer) -> Chunk<Layer> {
+    Chunk {
+        length: layer_length(&layer),
+        data: layer,
+    }
+}
+
+/// Replaces all clip and envelope references with their new indices, or with zero if the
+/// referenced clip or envelope doesn't exist. Both keep the size of the references, see
+/// [Renumbering].
+fn remap_references(data: &mut [Tag], clips: &HashMap<u32, u32>, envelopes: &HashMap<u32, u32>) {
+    let mut remapped = RemappedReferences { clips, envelopes };
+    for tag in data {
+        ReferencesMut(&mut remapped).visit_tag_mut(tag);
+    }
+}
+
+/// The clips and envelopes referred to by the visited surfaces and clips
+#[derive(Default)]
+struct UsedReferences {
+    clips: HashSet<u32>,
+    envelopes: HashSet<u32>,
+}
+
+impl ReferenceSink for UsedReferences {
+    fn clip(&mut self, _path: &'static [&'static str], index: &u32) {
+        self.clips.insert(*index);
+    }
+
+    fn envelope(&mut self, _path: &'static [&'static str], index: &u32) {
+        self.envelopes.insert(*index);
+    }
+}
+
+struct RemappedReferences<'a> {
+    clips: &'a HashMap<u32, u32>,
+    envelopes: &'a HashMap<u32, u32>,
+}
+
+impl ReferenceSinkMut for RemappedReferences<'_> {
+    fn clip(&mut self, _path: &'static [&'static str], index: &mut u32) {
+        *index = self.clips.get(index).copied().unwrap_or(0);
+    }
+
+    fn envelope(&mut self, _path: &'static [&'static str], index: &mut u32) {
+        *index = self.envelopes.get(index).copied().unwrap_or(0);
+    }
+}
@@ -0,0 +1,269 @@
+//! Geometric transforms that keep all spatial data of an object consistent, not just the points.
+
+use crate::geometry::math::{
+    apply3, cross, determinant3, dot, inverse3, length, linear, linear_part, normalize, scale, sub,
+    transform_point, transpose3, Mat4, Vec3,
+};
+use crate::lwo2::sub_tags::blocks::image_texture::SurfaceBlockImageTextureSubChunk;
+use crate::lwo2::sub_tags::blocks::texture_mapping::{
+    FalloffType, TextureMapping, TextureMappingSubChunk,
+};
+use crate::lwo2::tags::bounding_box::BoundingBox;
+use crate::lwo2::tags::discontinuous_vertex_mapping::DiscontinuousVertexMappings;
+use crate::lwo2::tags::layer::Layer;
+use crate::lwo2::tags::point_list::PointList;
+use crate::lwo2::tags::polygon_list::{PolygonKind, PolygonLists};
+use crate::lwo2::tags::vertex_mapping::{VertexMapKind, VertexMappings};
+use crate::visit::{walk_image_texture_attribute_mut, VisitorMut};
+use crate::LightWaveObject;
+
+impl LightWaveObject {
+    /// Applies an affine transform, given as a row-major matrix applied to column vectors, to
+    /// everything in the object that has a position, direction or size:
+    ///
+    /// * points, absolute morphs (SPOT) and bounding boxes
+    /// * layer pivots. Since points are stored relative to the pivot of their layer, they only
+    ///   receive the linear part of the transform.
+    /// * displacements (MORF) and normals (NORM)
+    /// * the center, size, rotation and falloff of texture mappings
+    ///
+    /// Mirroring transforms reverse the vertex order of FACE and PTCH polygons, so that they keep
+    /// facing the same way. Envelopes that animate any of these values are left alone.
+    pub fn transform(&mut self, matrix: &[[f32; 4]; 4]) {
+        let reverse_winding = determinant3(linear_part(matrix)) < 0.0;
+        self.apply_transform(*matrix, reverse_winding)
+    }
+
+    /// Scales the object uniformly, for example by 100 to convert meters to centimeters
+    pub fn scale_units(&mut self, factor: f32) {
+        let matrix = linear([[factor, 0.0, 0.0], [0.0, factor, 0.0], [0.0, 0.0, factor]]);
+        self.transform(&matrix)
+    }
+
+    /// Converts from LightWave's left-handed, Y-up coordinates to the right-handed, Z-up
+    /// coordinates of applications like Blender by swapping Y and Z.
+    ///
+    /// Unlike mirroring with [LightWaveObject::transform], the vertex order is kept: polygons
+    /// that appear clockwise in the left-handed system appear counter-clockwise in the
+    /// right-handed one, which is what right-handed applications expect of front faces.
+    pub fn to_right_handed_z_up(&mut self) {
+        self.apply_transform(
+            [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+            false,
+        )
+    }
+
+    fn apply_transform(&mut self, matrix: Mat4, reverse_winding: bool) {
+        let linear = linear_part(&matrix);
+        Transformer {
+            matrix,
+            linear,
+            normal_matrix: inverse3(linear).map_or(linear, transpose3),
+            reverse_winding,
+            in_layer: false,
+        }
+        .visit_object_mut(self)
+    }
+}
+
+struct Transformer {
+    matrix: Mat4,
+    linear: [Vec3; 3],
+    /// The inverse transpose of the linear part, which keeps normals perpendicular to surfaces
+    normal_matrix: [Vec3; 3],
+    reverse_winding: bool,
+    /// Whether a LAYR came before, so that points are relative to its pivot
+    in_layer: bool,
+}
+
+impl Transformer {
+    fn point(&self, point: Vec3) -> Vec3 {
+        if self.in_layer {
+            apply3(self.linear, point)
+        } else {
+            transform_point(&self.matrix, point)
+        }
+    }
+
+    fn normal(&self, normal: Vec3) -> Vec3 {
+        normalize(apply3(self.normal_matrix, normal)).unwrap_or(normal)
+    }
+
+    /// Calls `f` with every three-component value of a vertex map
+    fn values(values: &mut [f32], f: impl Fn(Vec3) -> Vec3) {
+        if let [x, y, z] = values {
+            [*x, *y, *z] = f([*x, *y, *z]);
+        }
+    }
+
+    fn texture_mapping(&self, mapping: &mut TextureMapping) {
+        let mut size = [1.0; 3];
+        let mut rotation = [0.0; 3];
+        for attribute in &mapping.attributes {
+            match attribute {
+                TextureMappingSubChunk::Size(value) => size = value.base_color,
+                TextureMappingSubChunk::Rotation(value) => rotation = value.base_color,
+                _ => (),
+            }
+        }
+        // the texture's axes, scaled by its size, in the transformed space
+        let columns = transpose3(rotation_matrix(rotation));
+        let axes = [0, 1, 2].map(|axis| scale(apply3(self.linear, columns[axis]), size[axis]));
+        let new_size = [0, 1, 2].map(|axis| length(axes[axis]));
+        let new_rotation = orthonormal_frame(axes)
+            .map(|frame| heading_pitch_bank(transpose3(frame)))
+            .unwrap_or(rotation);
+        let inverse_transpose = self.normal_matrix.map(|row| row.map(f32::abs));
+
+        for attribute in &mut mapping.attributes {
+            match attribute {
+                TextureMappingSubChunk::Center(value) => {
+                    value.base_color = transform_point(&self.matrix, value.base_color)
+                }
+                TextureMappingSubChunk::Size(value) => value.base_color = new_size,
+                TextureMappingSubChunk::Rotation(value) => value.base_color = new_rotation,
+                TextureMappingSubChunk::Falloff(falloff) => {
+                    // falloff is a rate per distance, so it scales inversely
+                    falloff.vector = apply3(inverse_transpose, falloff.vector);
+                    let axis = match falloff.kind {
+                        FalloffType::LinearX => Some(0),
+                        FalloffType::LinearY => Some(1),
+                        FalloffType::LinearZ => Some(2),
+                        FalloffType::Cubic | FalloffType::Spherical => None,
+                    };
+                    if let Some(axis) = axis {
+                        let mut direction = [0.0; 3];
+                        direction[axis] = 1.0;
+                        let direction = apply3(self.linear, direction).map(f32::abs);
+                        falloff.kind = if direction[0] >= direction[1].max(direction[2]) {
+                            FalloffType::LinearX
+                        } else if direction[1] >= direction[2] {
+                            FalloffType::LinearY
+                        } else {
+                            FalloffType::LinearZ
+                        };
+                    }
+                }
+                TextureMappingSubChunk::ReferenceObject(_)
+                | TextureMappingSubChunk::CoordinateSystem(_) => (),
+            }
+        }
+    }
+}
+
+impl VisitorMut for Transformer {
+    fn visit_layer_mut(&mut self, layer: &mut Layer) {
+        layer.pivot = transform_point(&self.matrix, layer.pivot);
+        self.in_layer = true;
+    }
+
+    fn visit_point_list_mut(&mut self, points: &mut PointList) {
+        for point in &mut points.point_location {
+            *point = self.point(*point);
+        }
+    }
+
+    fn visit_vertex_mappings_mut(&mut self, mappings: &mut VertexMappings) {
+        for mapping in &mut mappings.mapping {
+            match mappings.kind {
+                VertexMapKind::AbsoluteMorph => {
+                    Self::values(&mut mapping.value, |point| self.point(point))
+                }
+                VertexMapKind::Morph => Self::values(&mut mapping.value, |displacement| {
+                    apply3(self.linear, displacement)
+                }),
+                VertexMapKind::Normal => {
+                    Self::values(&mut mapping.value, |normal| self.normal(normal))
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn visit_discontinuous_vertex_mappings_mut(
+        &mut self,
+        mappings: &mut DiscontinuousVertexMappings,
+    ) {
+        if mappings.kind == VertexMapKind::Normal {
+            for mapping in &mut mappings.mappings {
+                Self::values(&mut mapping.values, |normal| self.normal(normal));
+            }
+        }
+    }
+
+    fn visit_bounding_box_mut(&mut self, bounding_box: &mut BoundingBox) {
+        let (min, max) = (bounding_box.min, bounding_box.max);
+        bounding_box.min = [f32::INFINITY; 3];
+        bounding_box.max = [f32::NEG_INFINITY; 3];
+        for corner in 0..8 {
+            let corner = [0, 1, 2].map(|axis| {
+                if corner >> axis & 1 == 0 {
+                    min[axis]
+                } else {
+                    max[axis]
+                }
+            });
+            let corner = self.point(corner);
+            bounding_box.min = [0, 1, 2].map(|axis| bounding_box.min[axis].min(corner[axis]));
+            bounding_box.max = [0, 1, 2].map(|axis| bounding_box.max[axis].max(corner[axis]));
+        }
+    }
+
+    fn visit_polygon_lists_mut(&mut self, polygons: &mut PolygonLists) {
+        if self.reverse_winding && matches!(polygons.kind, PolygonKind::Face | PolygonKind::Patch) {
+            for polygon in &mut polygons.polygons {
+                if let Some((_, rest)) = polygon.vert.split_first_mut() {
+                    rest.reverse();
+                }
+            }
+        }
+    }
+
+    fn visit_image_texture_attribute_mut(
+        &mut self,
+        attribute: &mut SurfaceBlockImageTextureSubChunk,
+    ) {
+        if let SurfaceBlockImageTextureSubChunk::TextureMapping(mapping) = attribute {
+            self.texture_mapping(mapping);
+        }
+        walk_image_texture_attribute_mut(self, attribute)
+    }
+}
+
+/// The rotation of heading (around Y), pitch (around X) and bank (around Z) angles in radians,
+/// applied in the order bank, pitch, heading
+fn rotation_matrix([heading, pitch, bank]: Vec3) -> [Vec3; 3] {
+    let (sh, ch) = heading.sin_cos();
+    let (sp, cp) = pitch.sin_cos();
+    let (sb, cb) = bank.sin_cos();
+    [
+        [ch * cb + sh * sp * sb, -ch * sb + sh * sp * cb, sh * cp],
+        [cp * sb, cp * cb, -sp],
+        [-sh * cb + ch * sp * sb, sh * sb + ch * sp * cb, ch * cp],
+    ]
+}
+
+/// The inverse of [rotation_matrix]
+fn heading_pitch_bank(m: [Vec3; 3]) -> Vec3 {
+    let pitch = (-m[1][2]).clamp(-1.0, 1.0).asin();
+    if m[1][2].abs() < 1.0 - 1e-6 {
+        [m[0][2].atan2(m[2][2]), pitch, m[1][0].atan2(m[1][1])]
+    } else {
+        // gimbal lock, where heading and bank rotate around the same axis
+        [(-m[2][0]).atan2(m[0][0]), pitch, 0.0]
+    }
+}
+
+/// Turns three axes into the rows of a rotation, keeping the direction of the first axis and
+/// flipping the last one if the axes are mirrored
+fn orthonormal_frame([x, y, _]: [Vec3; 3]) -> Option<[Vec3; 3]> {
+    let x = normalize(x)?;
+    let y = normalize(sub(y, scale(x, dot(y, x))))?;
+    let z = cross(x, y);
+    Some([x, y, z])
+}
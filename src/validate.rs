@@ -0,0 +1,325 @@
+use crate::lwo2::string::LwoString;
+use crate::lwo2::tags::polygon_tag_mapping::PolygonTagKind;
+use crate::lwo2::tags::Tag;
+use crate::references::{ReferenceSink, References};
+use crate::visit::Visitor;
+use crate::LightWaveObject;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// A single referential integrity problem found by [LightWaveObject::validate].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// Index of the offending chunk in [LightWaveObject::data]
+    pub tag_index: usize,
+    /// The layer number the chunk belongs to, if any layer was started before it
+    pub layer: Option<u16>,
+    /// The chunk ID followed by the IDs of all subchunks leading to the problem,
+    /// for example `["SURF", "BLOK", "IMAG"]`
+    pub path: Vec<&'static str>,
+    pub kind: ValidationErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    /// A POLS, VMAP or VMAD chunk appears before any PNTS chunk of its layer.
+    MissingPointList,
+    /// A PTAG or VMAD chunk appears before any POLS chunk of its layer.
+    MissingPolygonList,
+    /// A PTAG chunk appears before any TAGS chunk.
+    MissingTagStrings,
+    PointIndexOutOfRange {
+        index: u32,
+        point_count: usize,
+    },
+    PolygonIndexOutOfRange {
+        index: u32,
+        polygon_count: usize,
+    },
+    TagIndexOutOfRange {
+        index: u16,
+        tag_count: usize,
+    },
+    /// A clip index that no CLIP chunk defines.
+    MissingClip {
+        index: u32,
+    },
+    /// An envelope index that no ENVL chunk defines.
+    MissingEnvelope {
+        index: u32,
+    },
+    /// A tag string assigned through a SURF polygon tag, or the source of a SURF chunk,
+    /// without a matching SURF chunk.
+    MissingSurface {
+        name: String,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (chunk #{}", self.path.join(" > "), self.tag_index)?;
+        if let Some(layer) = self.layer {
+            write!(f, ", layer {}", layer)?;
+        }
+        write!(f, "): {}", self.kind)
+    }
+}
+
+impl fmt::Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingPointList => write!(f, "no preceding point list"),
+            Self::MissingPolygonList => write!(f, "no preceding polygon list"),
+            Self::MissingTagStrings => write!(f, "no preceding tag strings"),
+            Self::PointIndexOutOfRange { index, point_count } => {
+                write!(f, "point {} out of range ({} points)", index, point_count)
+            }
+            Self::PolygonIndexOutOfRange {
+                index,
+                polygon_count,
+            } => write!(
+                f,
+                "polygon {} out of range ({} polygons)",
+                index, polygon_count
+            ),
+            Self::TagIndexOutOfRange { index, tag_count } => {
+                write!(f, "tag {} out of range ({} tags)", index, tag_count)
+            }
+            Self::MissingClip { index } => write!(f, "clip {} does not exist", index),
+            Self::MissingEnvelope { index } => write!(f, "envelope {} does not exist", index),
+            Self::MissingSurface { name } => write!(f, "surface \"{}\" is not defined", name),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl LightWaveObject {
+    /// Checks that all indices and names used by the chunks of this object refer to something
+    /// that actually exists, i.e. points, polygons and tags of the most recent PNTS, POLS and
+    /// TAGS chunks, as well as clips, envelopes and surfaces anywhere in the file.
+    ///
+    /// All problems are collected instead of stopping at the first one.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut validator = Validator::default();
+        for (index, tag) in self.data.iter().enumerate() {
+            validator.tag_index = index;
+            validator.tag(tag);
+        }
+        validator.finish(self)
+    }
+}
+
+#[derive(Default)]
+struct Validator {
+    tag_index: usize,
+    layer: Option<u16>,
+    point_count: Option<usize>,
+    polygon_count: Option<usize>,
+    tag_strings: Option<Vec<LwoString>>,
+    errors: Vec<ValidationError>,
+    clip_references: Vec<(usize, Option<u16>, Vec<&'static str>, u32)>,
+    envelope_references: Vec<(usize, Option<u16>, Vec<&'static str>, u32)>,
+    surface_references: BTreeMap<String, (usize, Option<u16>, Vec<&'static str>)>,
+}
+
+impl Validator {
+    fn error(&mut self, path: &[&'static str], kind: ValidationErrorKind) {
+        self.errors.push(ValidationError {
+            tag_index: self.tag_index,
+            layer: self.layer,
+            path: path.to_vec(),
+            kind,
+        })
+    }
+
+    fn point(&mut self, path: &[&'static str], index: u32) {
+        match self.point_count {
+            Some(point_count) if index as usize >= point_count => self.error(
+                path,
+                ValidationErrorKind::PointIndexOutOfRange { index, point_count },
+            ),
+            _ => (),
+        }
+    }
+
+    fn polygon(&mut self, path: &[&'static str], index: u32) {
+        match self.polygon_count {
+            Some(polygon_count) if index as usize >= polygon_count => self.error(
+                path,
+                ValidationErrorKind::PolygonIndexOutOfRange {
+                    index,
+                    polygon_count,
+                },
+            ),
+            _ => (),
+        }
+    }
+
+    fn require_points(&mut self, path: &[&'static str]) {
+        if self.point_count.is_none() {
+            self.error(path, ValidationErrorKind::MissingPointList)
+        }
+    }
+
+    fn require_polygons(&mut self, path: &[&'static str]) {
+        if self.polygon_count.is_none() {
+            self.error(path, ValidationErrorKind::MissingPolygonList)
+        }
+    }
+
+    fn tag(&mut self, tag: &Tag) {
+        match tag {
+            Tag::Layer(layer) => {
+                self.layer = Some(layer.number);
+                self.point_count = None;
+                self.polygon_count = None;
+            }
+            Tag::PointList(points) => {
+                self.point_count = Some(points.point_location.len());
+                self.polygon_count = None;
+            }
+            Tag::PolygonList(polygons) => {
+                let path = ["POLS"];
+                self.require_points(&path);
+                for polygon in &polygons.polygons {
+                    for &vert in &polygon.vert {
+                        self.point(&path, vert);
+                    }
+                }
+                self.polygon_count = Some(polygons.polygons.len());
+            }
+            Tag::TagStrings(tags) => self.tag_strings = Some(tags.tag_strings.clone()),
+            Tag::PolygonTagMapping(mappings) => {
+                let path = ["PTAG"];
+                self.require_polygons(&path);
+                let tag_strings = match &self.tag_strings {
+                    Some(tag_strings) => tag_strings.clone(),
+                    None => return self.error(&path, ValidationErrorKind::MissingTagStrings),
+                };
+                for mapping in &mappings.mappings {
+                    self.polygon(&path, mapping.poly);
+                    // Smoothing groups and the like store plain numbers instead of tag indices
+                    if !mappings.kind.uses_tag_strings() {
+                        continue;
+                    }
+                    match tag_strings.get(mapping.tag as usize) {
+                        Some(name) if mappings.kind == PolygonTagKind::Surface => {
+                            self.surface_references.entry(name.to_string()).or_insert((
+                                self.tag_index,
+                                self.layer,
+                                vec!["PTAG"],
+                            ));
+                        }
+                        Some(_) => (),
+                        None => self.error(
+                            &path,
+                            ValidationErrorKind::TagIndexOutOfRange {
+                                index: mapping.tag,
+                                tag_count: tag_strings.len(),
+                            },
+                        ),
+                    }
+                }
+            }
+            Tag::VertexMapping(mappings) => {
+                let path = ["VMAP"];
+                self.require_points(&path);
+                for mapping in &mappings.mapping {
+                    self.point(&path, mapping.vert);
+                }
+            }
+            Tag::DiscontinuousVertexMapping(mappings) => {
+                let path = ["VMAD"];
+                self.require_points(&path);
+                self.require_polygons(&path);
+                for mapping in &mappings.mappings {
+                    self.point(&path, mapping.vert);
+                    self.polygon(&path, mapping.poly);
+                }
+            }
+            Tag::ImageClip(_) | Tag::SurfaceDefinition(_) => References(self).visit_tag(tag),
+            _ => (),
+        }
+    }
+
+    fn finish(mut self, object: &LightWaveObject) -> Result<(), Vec<ValidationError>> {
+        let mut clips = HashSet::new();
+        let mut envelopes = HashSet::new();
+        let mut surfaces = HashSet::new();
+        for tag in &object.data {
+            match tag {
+                Tag::ImageClip(clip) => {
+                    clips.insert(clip.index);
+                }
+                Tag::EnvelopeDefinition(envelope) => {
+                    envelopes.insert(envelope.index);
+                }
+                Tag::SurfaceDefinition(surface) => {
+                    surfaces.insert(surface.name.as_str());
+                }
+                _ => (),
+            }
+        }
+
+        for (tag_index, layer, path, index) in self.clip_references {
+            if !clips.contains(&index) {
+                self.errors.push(ValidationError {
+                    tag_index,
+                    layer,
+                    path,
+                    kind: ValidationErrorKind::MissingClip { index },
+                })
+            }
+        }
+        for (tag_index, layer, path, index) in self.envelope_references {
+            if !envelopes.contains(&index) {
+                self.errors.push(ValidationError {
+                    tag_index,
+                    layer,
+                    path,
+                    kind: ValidationErrorKind::MissingEnvelope { index },
+                })
+            }
+        }
+        for (name, (tag_index, layer, path)) in self.surface_references {
+            if !surfaces.contains(name.as_str()) {
+                self.errors.push(ValidationError {
+                    tag_index,
+                    layer,
+                    path,
+                    kind: ValidationErrorKind::MissingSurface { name },
+                })
+            }
+        }
+
+        self.errors.sort_by_key(|error| error.tag_index);
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// Clip and envelope references can point forward in the file, so they are only collected here
+/// and resolved once all chunks have been seen, as are the sources of surfaces.
+impl ReferenceSink for Validator {
+    fn clip(&mut self, path: &'static [&'static str], index: &u32) {
+        self.clip_references
+            .push((self.tag_index, self.layer, path.to_vec(), *index))
+    }
+
+    fn envelope(&mut self, path: &'static [&'static str], index: &u32) {
+        self.envelope_references
+            .push((self.tag_index, self.layer, path.to_vec(), *index))
+    }
+
+    fn surface(&mut self, path: &'static [&'static str], name: &LwoString) {
+        self.surface_references.entry(name.to_string()).or_insert((
+            self.tag_index,
+            self.layer,
+            path.to_vec(),
+        ));
+    }
+}
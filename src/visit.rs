@@ -0,0 +1,410 @@
+//! Traversal of the object tree, including all nested subchunks.
+//!
+//! Every method of [Visitor] and [VisitorMut] has a default implementation that simply walks into
+//! the children of the node, so an implementation only needs to override the methods for the
+//! nodes it is interested in. To keep walking into the children of an overridden node, call the
+//! matching `walk_*` function from the override.
+//!
+//! ```
+//! use lightwave_3d::lwo2::sub_tags::blocks::image_texture::SurfaceBlockImageTextureSubChunk;
+//! use lightwave_3d::visit::Visitor;
+//!
+//! #[derive(Default)]
+//! struct ImageMaps(Vec<u32>);
+//!
+//! impl Visitor for ImageMaps {
+//!     fn visit_image_texture_attribute(&mut self, attribute: &SurfaceBlockImageTextureSubChunk) {
+//!         if let SurfaceBlockImageTextureSubChunk::ImageMap(image) = attribute {
+//!             self.0.push(image.texture_image)
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::lwo2::sub_tags::blocks::gradient_texture::GradientTextureSubChunk;
+use crate::lwo2::sub_tags::blocks::image_texture::SurfaceBlockImageTextureSubChunk;
+use crate::lwo2::sub_tags::blocks::procedural_texture::ProceduralTextureSubChunk;
+use crate::lwo2::sub_tags::blocks::texture_mapping::TextureMappingSubChunk;
+use crate::lwo2::sub_tags::blocks::{Algorithm, SurfaceBlockHeaderSubChunk, SurfaceBlocks};
+use crate::lwo2::sub_tags::surface_parameters::SurfaceParameterSubChunk;
+use crate::lwo2::tags::bounding_box::BoundingBox;
+use crate::lwo2::tags::discontinuous_vertex_mapping::DiscontinuousVertexMappings;
+use crate::lwo2::tags::envelope::{EnvelopeDefinition, EnvelopeSubChunk};
+use crate::lwo2::tags::image_clip::{ImageClip, ImageClipSubChunk};
+use crate::lwo2::tags::layer::Layer;
+use crate::lwo2::tags::meta::{DescriptionLine, ThumbnailIconImage};
+use crate::lwo2::tags::point_list::PointList;
+use crate::lwo2::tags::polygon_list::PolygonLists;
+use crate::lwo2::tags::polygon_tag_mapping::PolygonTagMappings;
+use crate::lwo2::tags::surface_definition::SurfaceDefinition;
+use crate::lwo2::tags::tag_strings::TagStrings;
+use crate::lwo2::tags::vertex_map_parameter::VertexMapParameter;
+use crate::lwo2::tags::vertex_mapping::VertexMappings;
+use crate::lwo2::tags::Tag;
+use crate::LightWaveObject;
+
+pub trait Visitor {
+    fn visit_object(&mut self, object: &LightWaveObject) {
+        walk_object(self, object)
+    }
+
+    fn visit_tag(&mut self, tag: &Tag) {
+        walk_tag(self, tag)
+    }
+
+    fn visit_layer(&mut self, _layer: &Layer) {}
+
+    fn visit_point_list(&mut self, _points: &PointList) {}
+
+    fn visit_vertex_mappings(&mut self, _mappings: &VertexMappings) {}
+
+    fn visit_tag_strings(&mut self, _tags: &TagStrings) {}
+
+    fn visit_polygon_tag_mappings(&mut self, _mappings: &PolygonTagMappings) {}
+
+    fn visit_discontinuous_vertex_mappings(&mut self, _mappings: &DiscontinuousVertexMappings) {}
+
+    fn visit_vertex_map_parameter(&mut self, _parameter: &VertexMapParameter) {}
+
+    fn visit_bounding_box(&mut self, _bounding_box: &BoundingBox) {}
+
+    fn visit_description_line(&mut self, _description: &DescriptionLine) {}
+
+    fn visit_commentary_text(&mut self, _text: &DescriptionLine) {}
+
+    fn visit_thumbnail_icon_image(&mut self, _icon: &ThumbnailIconImage) {}
+
+    fn visit_polygon_lists(&mut self, _polygons: &PolygonLists) {}
+
+    fn visit_surface_definition(&mut self, surface: &SurfaceDefinition) {
+        walk_surface_definition(self, surface)
+    }
+
+    fn visit_surface_parameter(&mut self, attribute: &SurfaceParameterSubChunk) {
+        walk_surface_parameter(self, attribute)
+    }
+
+    fn visit_surface_block(&mut self, block: &SurfaceBlocks) {
+        walk_surface_block(self, block)
+    }
+
+    fn visit_surface_block_header_attribute(&mut self, _attribute: &SurfaceBlockHeaderSubChunk) {}
+
+    fn visit_image_texture_attribute(&mut self, attribute: &SurfaceBlockImageTextureSubChunk) {
+        walk_image_texture_attribute(self, attribute)
+    }
+
+    fn visit_procedural_texture_attribute(&mut self, _attribute: &ProceduralTextureSubChunk) {}
+
+    fn visit_gradient_texture_attribute(&mut self, _attribute: &GradientTextureSubChunk) {}
+
+    fn visit_shader_algorithm(&mut self, _algorithm: &Algorithm) {}
+
+    fn visit_texture_mapping_attribute(&mut self, _attribute: &TextureMappingSubChunk) {}
+
+    fn visit_image_clip(&mut self, clip: &ImageClip) {
+        walk_image_clip(self, clip)
+    }
+
+    fn visit_image_clip_attribute(&mut self, _attribute: &ImageClipSubChunk) {}
+
+    fn visit_envelope_definition(&mut self, envelope: &EnvelopeDefinition) {
+        walk_envelope_definition(self, envelope)
+    }
+
+    fn visit_envelope_attribute(&mut self, _attribute: &EnvelopeSubChunk) {}
+}
+
+pub fn walk_object<V: Visitor + ?Sized>(visitor: &mut V, object: &LightWaveObject) {
+    for tag in &object.data {
+        visitor.visit_tag(tag)
+    }
+}
+
+pub fn walk_tag<V: Visitor + ?Sized>(visitor: &mut V, tag: &Tag) {
+    match tag {
+        Tag::Layer(layer) => visitor.visit_layer(layer),
+        Tag::PointList(points) => visitor.visit_point_list(points),
+        Tag::VertexMapping(mappings) => visitor.visit_vertex_mappings(mappings),
+        Tag::TagStrings(tags) => visitor.visit_tag_strings(tags),
+        Tag::PolygonTagMapping(mappings) => visitor.visit_polygon_tag_mappings(mappings),
+        Tag::DiscontinuousVertexMapping(mappings) => {
+            visitor.visit_discontinuous_vertex_mappings(mappings)
+        }
+        Tag::VertexMapParameter(parameter) => visitor.visit_vertex_map_parameter(parameter),
+        Tag::BoundingBox(bounding_box) => visitor.visit_bounding_box(bounding_box),
+        Tag::DescriptionLine(description) => visitor.visit_description_line(description),
+        Tag::CommentaryText(text) => visitor.visit_commentary_text(text),
+        Tag::ThumbnailIconImage(icon) => visitor.visit_thumbnail_icon_image(icon),
+        Tag::PolygonList(polygons) => visitor.visit_polygon_lists(polygons),
+        Tag::SurfaceDefinition(surface) => visitor.visit_surface_definition(surface),
+        Tag::ImageClip(clip) => visitor.visit_image_clip(clip),
+        Tag::EnvelopeDefinition(envelope) => visitor.visit_envelope_definition(envelope),
+    }
+}
+
+pub fn walk_surface_definition<V: Visitor + ?Sized>(visitor: &mut V, surface: &SurfaceDefinition) {
+    for attribute in &surface.attributes {
+        visitor.visit_surface_parameter(attribute)
+    }
+}
+
+pub fn walk_surface_parameter<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    attribute: &SurfaceParameterSubChunk,
+) {
+    if let SurfaceParameterSubChunk::Blocks(block) = attribute {
+        visitor.visit_surface_block(block)
+    }
+}
+
+pub fn walk_surface_block<V: Visitor + ?Sized>(visitor: &mut V, block: &SurfaceBlocks) {
+    let header = match block {
+        SurfaceBlocks::ImageMapTexture { header, .. }
+        | SurfaceBlocks::ProceduralTexture { header, .. }
+        | SurfaceBlocks::GradientTexture { header, .. }
+        | SurfaceBlocks::ShaderPlugin { header, .. } => header,
+    };
+    for attribute in &header.block_attributes {
+        visitor.visit_surface_block_header_attribute(attribute)
+    }
+
+    match block {
+        SurfaceBlocks::ImageMapTexture { attributes, .. } => {
+            for attribute in attributes {
+                visitor.visit_image_texture_attribute(attribute)
+            }
+        }
+        SurfaceBlocks::ProceduralTexture { attributes, .. } => {
+            for attribute in attributes {
+                visitor.visit_procedural_texture_attribute(attribute)
+            }
+        }
+        SurfaceBlocks::GradientTexture { attributes, .. } => {
+            for attribute in attributes {
+                visitor.visit_gradient_texture_attribute(attribute)
+            }
+        }
+        SurfaceBlocks::ShaderPlugin { algorithm, .. } => visitor.visit_shader_algorithm(algorithm),
+    }
+}
+
+pub fn walk_image_texture_attribute<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    attribute: &SurfaceBlockImageTextureSubChunk,
+) {
+    if let SurfaceBlockImageTextureSubChunk::TextureMapping(mapping) = attribute {
+        for attribute in &mapping.attributes {
+            visitor.visit_texture_mapping_attribute(attribute)
+        }
+    }
+}
+
+pub fn walk_image_clip<V: Visitor + ?Sized>(visitor: &mut V, clip: &ImageClip) {
+    for attribute in &clip.attributes {
+        visitor.visit_image_clip_attribute(attribute)
+    }
+}
+
+pub fn walk_envelope_definition<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    envelope: &EnvelopeDefinition,
+) {
+    for attribute in &envelope.attributes {
+        visitor.visit_envelope_attribute(attribute)
+    }
+}
+
+/// Like [Visitor], but with mutable access to every node so that structures can be rewritten
+/// in place.
+pub trait VisitorMut {
+    fn visit_object_mut(&mut self, object: &mut LightWaveObject) {
+        walk_object_mut(self, object)
+    }
+
+    fn visit_tag_mut(&mut self, tag: &mut Tag) {
+        walk_tag_mut(self, tag)
+    }
+
+    fn visit_layer_mut(&mut self, _layer: &mut Layer) {}
+
+    fn visit_point_list_mut(&mut self, _points: &mut PointList) {}
+
+    fn visit_vertex_mappings_mut(&mut self, _mappings: &mut VertexMappings) {}
+
+    fn visit_tag_strings_mut(&mut self, _tags: &mut TagStrings) {}
+
+    fn visit_polygon_tag_mappings_mut(&mut self, _mappings: &mut PolygonTagMappings) {}
+
+    fn visit_discontinuous_vertex_mappings_mut(
+        &mut self,
+        _mappings: &mut DiscontinuousVertexMappings,
+    ) {
+    }
+
+    fn visit_vertex_map_parameter_mut(&mut self, _parameter: &mut VertexMapParameter) {}
+
+    fn visit_bounding_box_mut(&mut self, _bounding_box: &mut BoundingBox) {}
+
+    fn visit_description_line_mut(&mut self, _description: &mut DescriptionLine) {}
+
+    fn visit_commentary_text_mut(&mut self, _text: &mut DescriptionLine) {}
+
+    fn visit_thumbnail_icon_image_mut(&mut self, _icon: &mut ThumbnailIconImage) {}
+
+    fn visit_polygon_lists_mut(&mut self, _polygons: &mut PolygonLists) {}
+
+    fn visit_surface_definition_mut(&mut self, surface: &mut SurfaceDefinition) {
+        walk_surface_definition_mut(self, surface)
+    }
+
+    fn visit_surface_parameter_mut(&mut self, attribute: &mut SurfaceParameterSubChunk) {
+        walk_surface_parameter_mut(self, attribute)
+    }
+
+    fn visit_surface_block_mut(&mut self, block: &mut SurfaceBlocks) {
+        walk_surface_block_mut(self, block)
+    }
+
+    fn visit_surface_block_header_attribute_mut(
+        &mut self,
+        _attribute: &mut SurfaceBlockHeaderSubChunk,
+    ) {
+    }
+
+    fn visit_image_texture_attribute_mut(
+        &mut self,
+        attribute: &mut SurfaceBlockImageTextureSubChunk,
+    ) {
+        walk_image_texture_attribute_mut(self, attribute)
+    }
+
+    fn visit_procedural_texture_attribute_mut(
+        &mut self,
+        _attribute: &mut ProceduralTextureSubChunk,
+    ) {
+    }
+
+    fn visit_gradient_texture_attribute_mut(&mut self, _attribute: &mut GradientTextureSubChunk) {}
+
+    fn visit_shader_algorithm_mut(&mut self, _algorithm: &mut Algorithm) {}
+
+    fn visit_texture_mapping_attribute_mut(&mut self, _attribute: &mut TextureMappingSubChunk) {}
+
+    fn visit_image_clip_mut(&mut self, clip: &mut ImageClip) {
+        walk_image_clip_mut(self, clip)
+    }
+
+    fn visit_image_clip_attribute_mut(&mut self, _attribute: &mut ImageClipSubChunk) {}
+
+    fn visit_envelope_definition_mut(&mut self, envelope: &mut EnvelopeDefinition) {
+        walk_envelope_definition_mut(self, envelope)
+    }
+
+    fn visit_envelope_attribute_mut(&mut self, _attribute: &mut EnvelopeSubChunk) {}
+}
+
+pub fn walk_object_mut<V: VisitorMut + ?Sized>(visitor: &mut V, object: &mut LightWaveObject) {
+    for tag in &mut object.data {
+        visitor.visit_tag_mut(tag)
+    }
+}
+
+pub fn walk_tag_mut<V: VisitorMut + ?Sized>(visitor: &mut V, tag: &mut Tag) {
+    match tag {
+        Tag::Layer(layer) => visitor.visit_layer_mut(layer),
+        Tag::PointList(points) => visitor.visit_point_list_mut(points),
+        Tag::VertexMapping(mappings) => visitor.visit_vertex_mappings_mut(mappings),
+        Tag::TagStrings(tags) => visitor.visit_tag_strings_mut(tags),
+        Tag::PolygonTagMapping(mappings) => visitor.visit_polygon_tag_mappings_mut(mappings),
+        Tag::DiscontinuousVertexMapping(mappings) => {
+            visitor.visit_discontinuous_vertex_mappings_mut(mappings)
+        }
+        Tag::VertexMapParameter(parameter) => visitor.visit_vertex_map_parameter_mut(parameter),
+        Tag::BoundingBox(bounding_box) => visitor.visit_bounding_box_mut(bounding_box),
+        Tag::DescriptionLine(description) => visitor.visit_description_line_mut(description),
+        Tag::CommentaryText(text) => visitor.visit_commentary_text_mut(text),
+        Tag::ThumbnailIconImage(icon) => visitor.visit_thumbnail_icon_image_mut(icon),
+        Tag::PolygonList(polygons) => visitor.visit_polygon_lists_mut(polygons),
+        Tag::SurfaceDefinition(surface) => visitor.visit_surface_definition_mut(surface),
+        Tag::ImageClip(clip) => visitor.visit_image_clip_mut(clip),
+        Tag::EnvelopeDefinition(envelope) => visitor.visit_envelope_definition_mut(envelope),
+    }
+}
+
+pub fn walk_surface_definition_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    surface: &mut SurfaceDefinition,
+) {
+    for attribute in &mut surface.attributes {
+        visitor.visit_surface_parameter_mut(attribute)
+    }
+}
+
+pub fn walk_surface_parameter_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    attribute: &mut SurfaceParameterSubChunk,
+) {
+    if let SurfaceParameterSubChunk::Blocks(block) = attribute {
+        visitor.visit_surface_block_mut(block)
+    }
+}
+
+pub fn walk_surface_block_mut<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut SurfaceBlocks) {
+    let header = match block {
+        SurfaceBlocks::ImageMapTexture { header, .. }
+        | SurfaceBlocks::ProceduralTexture { header, .. }
+        | SurfaceBlocks::GradientTexture { header, .. }
+        | SurfaceBlocks::ShaderPlugin { header, .. } => header,
+    };
+    for attribute in &mut header.block_attributes {
+        visitor.visit_surface_block_header_attribute_mut(attribute)
+    }
+
+    match block {
+        SurfaceBlocks::ImageMapTexture { attributes, .. } => {
+            for attribute in attributes {
+                visitor.visit_image_texture_attribute_mut(attribute)
+            }
+        }
+        SurfaceBlocks::ProceduralTexture { attributes, .. } => {
+            for attribute in attributes {
+                visitor.visit_procedural_texture_attribute_mut(attribute)
+            }
+        }
+        SurfaceBlocks::GradientTexture { attributes, .. } => {
+            for attribute in attributes {
+                visitor.visit_gradient_texture_attribute_mut(attribute)
+            }
+        }
+        SurfaceBlocks::ShaderPlugin { algorithm, .. } => {
+            visitor.visit_shader_algorithm_mut(algorithm)
+        }
+    }
+}
+
+pub fn walk_image_texture_attribute_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    attribute: &mut SurfaceBlockImageTextureSubChunk,
+) {
+    if let SurfaceBlockImageTextureSubChunk::TextureMapping(mapping) = attribute {
+        for attribute in &mut mapping.attributes {
+            visitor.visit_texture_mapping_attribute_mut(attribute)
+        }
+    }
+}
+
+pub fn walk_image_clip_mut<V: VisitorMut + ?Sized>(visitor: &mut V, clip: &mut ImageClip) {
+    for attribute in &mut clip.attributes {
+        visitor.visit_image_clip_attribute_mut(attribute)
+    }
+}
+
+pub fn walk_envelope_definition_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    envelope: &mut EnvelopeDefinition,
+) {
+    for attribute in &mut envelope.attributes {
+        visitor.visit_envelope_attribute_mut(attribute)
+    }
+}